@@ -1,8 +1,133 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::sim_logger::Verbosity;
 
 #[derive(Parser, Debug)]
 pub struct Args {
+    /// What to do; with no command, play the game
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Size the window so no grid cell is drawn smaller than PIXELS on a side (play and watch)
+    #[arg(long, global = true, value_name = "PIXELS")]
+    pub min_tile_pixels: Option<f32>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Play the game (the default)
+    Play,
+
+    /// Play today's daily challenge: the same board and apples for everyone playing today
+    Daily,
+
+    /// Train nets to play, saving each new champion into stash/
+    Train(TrainArgs),
+
+    /// Watch a recorded playback (e.g. stash/...-Playback.json, or a champion's stash/Net-....json) instead of playing
+    Watch {
+        #[arg(value_name = "FILE")]
+        playback: String,
+    },
+
+    /// Print a saved net's structure and its move on a new board, or on the end of --playback
+    Inspect {
+        #[arg(value_name = "NET_FILE")]
+        net: String,
+
+        /// Evaluate the net on the board at the end of this recorded playback
+        #[arg(long, value_name = "FILE")]
+        playback: Option<String>,
+    },
+
+    /// Time evaluating a saved net, e.g. to check that a change to net evaluation didn't slow it down
+    Bench {
+        #[arg(value_name = "NET_FILE")]
+        net: String,
+
+        /// How many times to evaluate the net
+        #[arg(long, default_value_t = 100_000)]
+        iterations: usize,
+    },
+
+    /// Report where two recorded playbacks' games diverge
+    DiffPlayback {
+        #[arg(value_name = "PLAYBACK_A")]
+        a: String,
+        #[arg(value_name = "PLAYBACK_B")]
+        b: String,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TrainArgs {
+    /// Also show debug output from the neural net internals
+    #[arg(long, short, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Only show new champions, eras, and cataclysms; no per-generation stats
+    #[arg(long, short)]
+    pub quiet: bool,
+
+    /// Write every net's fitness, one CSV file per generation, into DIR
+    #[arg(long, value_name = "DIR")]
+    pub dump_fitness: Option<String>,
+
+    /// Append each generation's stats (best, means, era, events) as a JSON line to FILE
+    #[arg(long, value_name = "FILE")]
+    pub metrics: Option<String>,
+
+    /// Round champions' saved weights to N decimal places, for smaller stash files
+    #[arg(long, value_name = "N")]
+    pub weight_decimals: Option<u32>,
+
+    /// Also save a PNG of each new champion's final board into stash/
+    #[arg(long)]
+    pub snapshots: bool,
+
+    /// Also save each new champion's per-move inputs, outputs, and moves as JSON into stash/
     #[arg(long)]
-    pub sim: bool,
+    pub traces: bool,
+
+    /// Continue the stopped run that wrote this checkpoint (e.g. stash/Checkpoint-Gen000123.json)
+    #[arg(long, value_name = "FILE")]
+    pub resume: Option<String>,
+}
+
+impl TrainArgs {
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (_, true) => Verbosity::Verbose,
+            _         => Verbosity::Normal,
+        }
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subcommands_parse() {
+        assert!(Args::try_parse_from(["snake-bevy"]).unwrap().command.is_none());
+        let Some(Command::Train(train_args)) = Args::try_parse_from(["snake-bevy", "train", "-q", "--weight-decimals", "3"]).unwrap().command else {
+            panic!("expected Train");
+        };
+        assert_eq!((Verbosity::Quiet, Some(3)), (train_args.verbosity(), train_args.weight_decimals));
+        let Some(Command::Inspect { net, playback }) = Args::try_parse_from(["snake-bevy", "inspect", "Net.json", "--playback", "P.json"]).unwrap().command else {
+            panic!("expected Inspect");
+        };
+        assert_eq!(("Net.json", Some("P.json")), (net.as_str(), playback.as_deref()));
+        let args = Args::try_parse_from(["snake-bevy", "watch", "P.json", "--min-tile-pixels", "24"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Watch { .. })));
+        assert_eq!(Some(24.0), args.min_tile_pixels);
+        let Some(Command::Bench { net, iterations }) = Args::try_parse_from(["snake-bevy", "bench", "Net.json"]).unwrap().command else {
+            panic!("expected Bench");
+        };
+        assert_eq!(("Net.json", 100_000), (net.as_str(), iterations));
+        assert!(Args::try_parse_from(["snake-bevy", "train", "-q", "-v"]).is_err());
+        assert!(Args::try_parse_from(["snake-bevy", "diff-playback", "A.json"]).is_err());
+    }
+}
@@ -4,7 +4,9 @@ use bevy::prelude::*;
 
 pub mod assets;
 pub mod audio;
+pub mod playback_viewer;
 pub mod snake_visualizer;
+pub mod visited_overlay;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -12,5 +14,7 @@ pub(super) fn plugin(app: &mut App) {
         assets::plugin,
 
         snake_visualizer::plugin,
+        playback_viewer::plugin,
+        visited_overlay::plugin,
     ));
 }
@@ -0,0 +1,182 @@
+//! Replays a recorded `Playback` (e.g. one embedded in a champion's `Net-....json` from `stash/`), with
+//! frame controls: Left/Right step one move, Space toggles auto-play, and +/- change the speed.
+//! Stepping backward simply replays from the start up to the target move.  When the file is a
+//! champion's, F3 logs its net's structure and outputs on the board being shown.
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::map::TilemapSize;
+use bevy_ecs_tilemap::tiles::TileStorage;
+
+use crate::neural_net::nets::Net;
+use crate::nn_plays_snake::{MyFitnessInfo, NnPlaysSnake};
+use crate::snake_game;
+use crate::AppSet;
+
+use super::snake_visualizer::redraw_tilemap;
+use super::snake_visualizer::MySnakeGame;
+use super::snake_visualizer::UpdateScore;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, record_scrubber_input.in_set(AppSet::RecordInput));
+    app.add_systems(Update, apply_scrubber.in_set(AppSet::Update));
+    app.add_systems(Update, dump_watched_net.in_set(AppSet::Update).after(apply_scrubber));
+}
+
+
+/// When present, the level shows this playback instead of a game the player controls, along
+/// with the net that played it if the file is a champion's.
+#[derive(Resource)]
+pub struct LoadedPlayback(pub(super) snake_game::Playback, pub(super) Option<Net<MyFitnessInfo>>);
+
+impl LoadedPlayback {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let playback = snake_game::Playback::from_file(path)?;
+        Ok(Self(playback, NnPlaysSnake::load_net(path).ok()))
+    }
+}
+
+
+/// The net that played the playback being watched, for F3 to describe.
+#[derive(Component)]
+pub(super) struct WatchedNet(pub(super) Net<MyFitnessInfo>);
+
+
+#[derive(Component, Debug)]
+pub(super) struct PlaybackScrubber {
+    index: usize,               // How many moves of the playback are shown
+    move_count: usize,
+    is_playing: bool,
+    moves_per_second: f64,
+    last_step: f64,
+    is_dirty: bool,             // Board needs to be re-derived for `index`
+}
+
+impl PlaybackScrubber {
+    const MIN_MOVES_PER_SECOND: f64 = 1.25;
+    const MAX_MOVES_PER_SECOND: f64 = 80.0;
+
+    pub(super) fn new(move_count: usize) -> Self {
+        Self { index: 0, move_count, is_playing: false, moves_per_second: 10.0, last_step: 0.0, is_dirty: false }
+    }
+
+    /// Moves the index by `delta` moves, clamped to [0, move_count].  Returns whether it changed.
+    fn step(&mut self, delta: isize) -> bool {
+        let new_index = self.index.saturating_add_signed(delta).min(self.move_count);
+        let has_changed = new_index != self.index;
+        self.index = new_index;
+        self.is_dirty |= has_changed;
+        has_changed
+    }
+
+    fn change_speed(&mut self, factor: f64) {
+        self.moves_per_second = (self.moves_per_second * factor).clamp(Self::MIN_MOVES_PER_SECOND, Self::MAX_MOVES_PER_SECOND);
+    }
+}
+
+
+fn record_scrubber_input(
+    input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut scrubber_query: Query<&mut PlaybackScrubber>,
+) {
+    for mut scrubber in &mut scrubber_query {
+        if input.just_pressed(KeyCode::ArrowRight) { scrubber.step(1); }
+        if input.just_pressed(KeyCode::ArrowLeft)  { scrubber.step(-1); }
+        if input.just_pressed(KeyCode::Equal)      { scrubber.change_speed(2.0); }
+        if input.just_pressed(KeyCode::Minus)      { scrubber.change_speed(0.5); }
+        if input.just_pressed(KeyCode::Space) {
+            // Playing from the very end starts over
+            if !scrubber.is_playing && scrubber.index == scrubber.move_count {
+                let move_count = scrubber.move_count as isize;
+                scrubber.step(-move_count);
+            }
+            scrubber.is_playing = !scrubber.is_playing;
+            scrubber.last_step = time.elapsed_seconds_f64();
+        }
+    }
+}
+
+fn apply_scrubber(
+    mut commands: Commands,
+    time: Res<Time>,
+    playback: Option<Res<LoadedPlayback>>,
+    mut snake_query: Query<(&mut MySnakeGame, &mut PlaybackScrubber)>,
+    mut tilemap_query: Query<(&mut TileStorage, &TilemapSize, Entity)>,
+) {
+    let Some(playback) = playback else { return; };
+    for (mut my_snake_game, mut scrubber) in snake_query.iter_mut() {
+        if scrubber.is_playing {
+            let current_time = time.elapsed_seconds_f64();
+            if current_time - scrubber.last_step > 1.0 / scrubber.moves_per_second {
+                scrubber.last_step = current_time;
+                if !scrubber.step(1) { scrubber.is_playing = false; }
+            }
+        }
+        if !scrubber.is_dirty { continue; }
+        scrubber.is_dirty = false;
+
+        my_snake_game.snake_game = snake_game::SnakeGame::from_playback_with_config(&playback.0.config, playback.0.prefix_through_move(scrubber.index));
+        let (mut tile_storage, map_size, tilemap_entity) = tilemap_query.get_single_mut().unwrap();
+        redraw_tilemap(&mut commands, &mut my_snake_game, tilemap_entity, &mut tile_storage, map_size);
+        commands.trigger(UpdateScore(my_snake_game.snake_game.apples_eaten));
+    }
+}
+
+fn is_dump_net_requested(input: &ButtonInput<KeyCode>) -> bool {
+    input.just_pressed(KeyCode::F3)
+}
+
+fn dump_watched_net(
+    input: Res<ButtonInput<KeyCode>>,
+    snake_query: Query<(&MySnakeGame, Option<&WatchedNet>), With<PlaybackScrubber>>,
+) {
+    if !is_dump_net_requested(&input) { return; }
+    for (my_snake_game, watched_net) in &snake_query {
+        let Some(WatchedNet(net)) = watched_net else {
+            info!("No net to dump; this playback isn't from a champion's file");
+            continue;
+        };
+        match NnPlaysSnake::describe_net_on(net, &my_snake_game.snake_game) {
+            Ok(report) => info!("Watched net:\n{report}"),
+            Err(e) => error!("ERROR describing the watched net: {e}"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_clamps_at_boundaries() {
+        let mut scrubber = PlaybackScrubber::new(3);
+        assert!(!scrubber.step(-1));
+        assert_eq!(0, scrubber.index);
+        assert!(!scrubber.is_dirty);
+        assert!(scrubber.step(2));
+        assert!(scrubber.step(5));
+        assert_eq!(3, scrubber.index);
+        assert!(!scrubber.step(1));
+        assert_eq!(3, scrubber.index);
+        assert!(scrubber.step(-10));
+        assert_eq!(0, scrubber.index);
+
+        let mut empty = PlaybackScrubber::new(0);
+        assert!(!empty.step(1));
+        assert_eq!(0, empty.index);
+    }
+
+    #[test]
+    fn test_dump_net_only_on_f3_press() {
+        let mut input = ButtonInput::<KeyCode>::default();
+        assert!(!is_dump_net_requested(&input));
+        input.press(KeyCode::F2);
+        assert!(!is_dump_net_requested(&input));
+        input.press(KeyCode::F3);
+        assert!(is_dump_net_requested(&input));
+        // Held down, it doesn't fire again on the next frame
+        input.clear();
+        assert!(!is_dump_net_requested(&input));
+    }
+}
@@ -4,8 +4,10 @@
 //! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
 
 use std::collections::VecDeque;
+use std::fs;
 
 use bevy::prelude::*;
+use chrono::{Datelike, NaiveDate};
 use bevy_ecs_tilemap::map::TilemapId;
 use bevy_ecs_tilemap::map::TilemapSize;
 use bevy_ecs_tilemap::map::TilemapTexture;
@@ -23,14 +25,19 @@ use crate::screen::Screen;
 use crate::snake_game;
 use crate::snake_game::GameState;
 use crate::AppSet;
+use crate::TILE_SIZE;
 
 use super::assets::HandleMap;
 use super::assets::ImageKey;
 use super::assets::SfxKey;
 use super::audio::sfx::PlaySfx;
+use super::playback_viewer::LoadedPlayback;
+use super::playback_viewer::PlaybackScrubber;
+use super::playback_viewer::WatchedNet;
+use super::visited_overlay::VisitedOverlayTile;
 
 #[derive(Event, Debug)]
-pub struct UpdateScore(usize);
+pub struct UpdateScore(pub(super) usize);
 
 
 
@@ -38,17 +45,55 @@ pub struct UpdateScore(usize);
 pub struct SpawnLevel;
 
 
+/// When present, every level the player plays is the day's challenge: its snake and apples all
+/// come from a seed derived from the date, so everyone playing that day can compare scores.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DailyChallenge {
+    pub date: NaiveDate,
+    pub seed: u64,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        Self::for_date(chrono::Local::now().date_naive())
+    }
+
+    pub fn for_date(date: NaiveDate) -> Self {
+        Self { date, seed: daily_seed(date) }
+    }
+
+    /// Shown beside the score, so players can tell they're on the same board.
+    fn label(&self) -> String {
+        format!("Daily {} (seed {})", self.date.format("%Y-%m-%d"), self.seed)
+    }
+}
+
+/// The seed of `date`'s daily challenge: the same on every machine, but unrelated from one day to
+/// the next (a SplitMix64 step of the day number).
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    let mut z = (date.num_days_from_ce() as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+
 #[derive(Component)]
-struct MySnakeGame {
-    snake_game: snake_game::SnakeGame,
-    location_apple_prev: snake_game::GridPoint,
-    location_tail_prev: snake_game::GridPoint,
+pub(super) struct MySnakeGame {
+    pub(super) snake_game: snake_game::SnakeGame,
+    pub(super) location_apple_prev: snake_game::GridPoint,
+    pub(super) location_tail_prev: snake_game::GridPoint,
 }
 
 pub(super) fn plugin(app: &mut App) {
     // Register (i.e. record) what movement the player takes via keyboard/etc.
     app.register_type::<SnakeMovementController>();
+    app.register_type::<KeyBindings>();
+    app.init_resource::<KeyBindings>();
+    app.register_type::<TickCadence>();
+    app.init_resource::<TickCadence>();
     app.add_systems(Update, record_movement_controller.in_set(AppSet::RecordInput));
+    app.add_systems(Update, save_playback_on_game_over.in_set(AppSet::RecordInput));
 
     // Apply movement based on controls.
     app.add_systems(Update, apply_movement.in_set(AppSet::Update));
@@ -59,6 +104,7 @@ pub(super) fn plugin(app: &mut App) {
     // We watch for these events:
     app.observe(spawn_level);
     app.observe(update_score);
+    app.observe(restart_level);
 }
 
 
@@ -92,33 +138,130 @@ pub struct SnakeMovementController {
     is_paused: bool,
 }
 
+impl SnakeMovementController {
+    /// The direction to step in given `intent`, from the player or from a net, or `None` while
+    /// paused, so P freezes every kind of play alike.
+    fn step_direction(&self, intent: Option<Dir>) -> Option<Dir> {
+        if self.is_paused { None } else { intent }
+    }
+}
+
+/// Everything the player can ask for from the keyboard.
+#[derive(Reflect, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Restart,
+    SavePlayback,
+    ToggleVisitedOverlay,
+}
+
+impl Action {
+    pub fn to_dir(self) -> Option<Dir> {
+        match self {
+            Action::Up    => Some(Dir::Up),
+            Action::Down  => Some(Dir::Down),
+            Action::Left  => Some(Dir::Left),
+            Action::Right => Some(Dir::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Maps keys to actions.  More than one key may be bound to the same action.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct KeyBindings {
+    pub bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (KeyCode::KeyW,       Action::Up),
+                (KeyCode::ArrowUp,    Action::Up),
+                (KeyCode::KeyS,       Action::Down),
+                (KeyCode::ArrowDown,  Action::Down),
+                (KeyCode::KeyA,       Action::Left),
+                (KeyCode::ArrowLeft,  Action::Left),
+                (KeyCode::KeyD,       Action::Right),
+                (KeyCode::ArrowRight, Action::Right),
+                (KeyCode::KeyP,       Action::Pause),
+                (KeyCode::Pause,      Action::Pause),
+                (KeyCode::KeyR,       Action::Restart),
+                (KeyCode::F2,         Action::SavePlayback),
+                (KeyCode::KeyV,       Action::ToggleVisitedOverlay),
+            ],
+        }
+    }
+}
+
+/// How often uneventful moves play the tick sound: every `every`th one, or never if `every` is 0.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct TickCadence {
+    pub every: u32,
+    moves_since_tick: u32,
+}
+
+impl Default for TickCadence {
+    fn default() -> Self {
+        Self { every: 1, moves_since_tick: 0 }
+    }
+}
+
+impl TickCadence {
+    /// Counts one uneventful move, returning whether it should tick.
+    pub fn should_tick(&mut self) -> bool {
+        if self.every == 0 { return false; }
+        self.moves_since_tick += 1;
+        if self.moves_since_tick < self.every { return false; }
+        self.moves_since_tick = 0;
+        true
+    }
+}
+
+impl KeyBindings {
+    /// True when any key bound to `action` satisfies `is_key` (e.g. "is pressed").
+    pub fn is_action(&self, action: Action, is_key: impl Fn(KeyCode) -> bool) -> bool {
+        self.bindings.iter().any(|&(key, a)| a == action && is_key(key))
+    }
+
+    /// The direction the player intends to move, given which keys are pressed, along with whether
+    /// any of the keys for that direction was just pressed.  When several directions are held,
+    /// the last one in Up/Down/Left/Right order wins.
+    pub fn movement_intent(&self, pressed: impl Fn(KeyCode) -> bool, just_pressed: impl Fn(KeyCode) -> bool) -> (Option<Dir>, bool) {
+        let mut intent = None;
+        let mut is_new = false;
+        for action in [Action::Up, Action::Down, Action::Left, Action::Right] {
+            if self.is_action(action, &pressed) {
+                intent = action.to_dir();
+                if self.is_action(action, &just_pressed) { is_new = true; }
+            }
+        }
+        (intent, is_new)
+    }
+}
+
 fn record_movement_controller(
+    mut commands: Commands,
     input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
     mut controller_query: Query<(&mut SnakeMovementController, &mut LastUpdate)>,
 ) {
     // Collect directional input.
-    let mut player_movement_intent = None;
-    let mut should_reset_timer = false;
     // FUTURE: Ignore reversing direction, since this always produces a crash
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
-        player_movement_intent = Some(Dir::Up);
-        if input.just_pressed(KeyCode::KeyW) || input.just_pressed(KeyCode::ArrowUp)    { should_reset_timer = true; }
-    }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
-        player_movement_intent = Some(Dir::Down);
-        if input.just_pressed(KeyCode::KeyS) || input.just_pressed(KeyCode::ArrowDown)  { should_reset_timer = true; }
-    }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
-        player_movement_intent = Some(Dir::Left);
-        if input.just_pressed(KeyCode::KeyA) || input.just_pressed(KeyCode::ArrowLeft)  { should_reset_timer = true; }
-    }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
-        player_movement_intent = Some(Dir::Right);
-        if input.just_pressed(KeyCode::KeyD) || input.just_pressed(KeyCode::ArrowRight) { should_reset_timer = true; }
-    }
+    let (player_movement_intent, should_reset_timer) = key_bindings.movement_intent(|k| input.pressed(k), |k| input.just_pressed(k));
 
-    let mut should_toggle_pause = false;
-    if input.just_pressed(KeyCode::KeyP) || input.just_pressed(KeyCode::Pause) { should_toggle_pause = true; }
+    let should_toggle_pause = key_bindings.is_action(Action::Pause, |k| input.just_pressed(k));
+    if key_bindings.is_action(Action::Restart, |k| input.just_pressed(k)) {
+        commands.trigger(RestartLevel);
+        return;
+    }
 
     // Apply movement intent to controllers.
     let player_intends_to_move = player_movement_intent.is_some();
@@ -135,6 +278,51 @@ fn record_movement_controller(
 }
 
 
+/// After a game over, writes the just-played game to `stash/` so it can be shared, or watched
+/// again with `watch <FILE>`.
+fn save_playback_on_game_over(
+    input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    snake_query: Query<&MySnakeGame, With<SnakeMovementController>>,
+) {
+    if !key_bindings.is_action(Action::SavePlayback, |k| input.just_pressed(k)) { return; }
+    for my_snake_game in &snake_query {
+        let snake_game = &my_snake_game.snake_game;
+        if snake_game.state != GameState::GameOver { continue; }
+        let date = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let filename = format!("stash/Game-{date}-Apples{}-Playback.json", snake_game.apples_eaten);
+        match write_playback(&snake_game.playback, &filename) {
+            Ok(()) => info!("Saved playback to {filename}"),
+            Err(e) => error!("ERROR saving playback to {filename}: {e}"),
+        }
+    }
+}
+
+fn write_playback(playback: &snake_game::Playback, filename: &str) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(playback).map_err(|e| e.to_string())?;
+    if let Some(dir) = std::path::Path::new(filename).parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(filename, s).map_err(|e| e.to_string())
+}
+
+
+#[derive(Event, Debug)]
+pub struct RestartLevel;
+
+/// Throws away the current game (and all of its tiles and UI) and spawns a fresh one.
+fn restart_level(
+    _trigger: Trigger<RestartLevel>,
+    mut commands: Commands,
+    level_query: Query<Entity, Or<(With<MySnakeGame>, With<TileStorage>, With<TilePos>, With<Score>, With<VisitedOverlayTile>)>>,
+) {
+    for entity in &level_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.trigger(SpawnLevel);
+}
+
+
 
 const TILE_CRASH:         u32 = 0;
 const TILE_APPLE:         u32 = 1;
@@ -277,14 +465,47 @@ fn copy_snake_into_tilemap(snake_locations: &VecDeque<snake_game::GridPoint>, ti
     }
 }
 
+/// Throws away every tile and redraws the whole tilemap from `my_snake_game`, e.g. after jumping
+/// to an arbitrary point in a playback, where update_tilemap()'s incremental approach won't do.
+pub(super) fn redraw_tilemap(
+    commands: &mut Commands,
+    my_snake_game: &mut MySnakeGame,
+    tilemap_entity: Entity,
+    tile_storage: &mut TileStorage,
+    map_size: &TilemapSize,
+) {
+    for tile in tile_storage.iter().flatten() {
+        commands.entity(*tile).despawn();
+    }
+    *tile_storage = TileStorage::empty(*map_size);
+
+    let snake_game = &my_snake_game.snake_game;
+    copy_grid_into_tilemap(&snake_game.grid, tilemap_entity, tile_storage, map_size, commands);
+    copy_snake_into_tilemap(&snake_game.snake.locations, tilemap_entity, tile_storage, commands);
+    if snake_game.state == GameState::GameOver {
+        let pt_head = snake_game.snake.locations[0];
+        let tile_head = tile_storage.get(&TilePos { x: pt_head.x as u32, y: pt_head.y as u32 }).unwrap();
+        commands.entity(tile_head).insert(TileTextureIndex(TILE_CRASH));
+    }
+    my_snake_game.location_apple_prev = snake_game.apple.location;
+    my_snake_game.location_tail_prev = snake_game.snake.locations[snake_game.snake.locations.len() - 1];
+}
+
 
 fn spawn_level(
     _trigger: Trigger<SpawnLevel>,
     mut commands: Commands,
     image_handles: Res<HandleMap<ImageKey>>,
+    playback: Option<Res<LoadedPlayback>>,
+    daily_challenge: Option<Res<DailyChallenge>>,
 ) {
-    // Create the underlying snake_game--essentially our data model
-    let snake_game = snake_game::SnakeGame::new(None);
+    // Create the underlying snake_game--essentially our data model.  A loaded playback starts at
+    // its first move, and a daily challenge (even when restarted) at the day's seeded board.
+    let snake_game = match (&playback, &daily_challenge) {
+        (Some(playback), _) => snake_game::SnakeGame::from_playback_with_config(&playback.0.config, playback.0.prefix_through_move(0)),
+        (None, Some(daily_challenge)) => snake_game::SnakeGame::from_seed(daily_challenge.seed),
+        (None, None) => snake_game::SnakeGame::new(None),
+    };
 
     // Create and insert the TileMap
     let tilemap_entity = commands.spawn_empty().id();
@@ -293,7 +514,7 @@ fn spawn_level(
     let map_type = TilemapType::Square;
     copy_grid_into_tilemap(&snake_game.grid, tilemap_entity, &mut tile_storage, &map_size, &mut commands);
     copy_snake_into_tilemap(&snake_game.snake.locations, tilemap_entity, &mut tile_storage, &mut commands);
-    let tile_pixel_size = TilemapTileSize { x: 16.0, y: 16.0 };
+    let tile_pixel_size = TilemapTileSize { x: TILE_SIZE.x, y: TILE_SIZE.y };
     let grid_size = tile_pixel_size.into();
     let texture_handle: Handle<Image> = image_handles[&ImageKey::SnakeTiles].clone_weak(); //asset_server.load("images/snake_tiles.png");
     commands.entity(tilemap_entity).insert(
@@ -312,27 +533,38 @@ fn spawn_level(
     // Init and insert the MySnakeGame
     let location_apple_prev = snake_game.apple.location;
     let location_tail_prev = snake_game.snake.locations[snake_game.snake.locations.len() - 1];
-    commands.spawn((
+    let mut snake_entity = commands.spawn((
         MySnakeGame { 
             snake_game,
             location_apple_prev,
             location_tail_prev,
         },
         LastUpdate(0.0),
-        SnakeMovementController { player_movement_intent: None, is_paused: false },
         StateScoped(Screen::Playing),
     ));
+    match &playback {
+        None => { snake_entity.insert(SnakeMovementController { player_movement_intent: None, is_paused: false }); }
+        Some(playback) => {
+            snake_entity.insert(PlaybackScrubber::new(playback.0.move_count()));
+            if let Some(net) = &playback.1 { snake_entity.insert(WatchedNet(net.clone())); }
+        }
+    }
 
-    // The Score
+    // The Score, followed by which daily challenge this is, if any (update_score() only changes the former)
+    let text_style = TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..default()
+    };
+    let daily_label = match (&playback, &daily_challenge) {
+        (None, Some(daily_challenge)) => format!("    {}", daily_challenge.label()),
+        _ => String::new(),
+    };
     commands.spawn((
-        TextBundle::from_section(
-            "Score: 0",
-            TextStyle {
-                font_size: 20.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
+        TextBundle::from_sections([
+            TextSection::new("Score: 0", text_style.clone()),
+            TextSection::new(daily_label, text_style),
+        ])
         .with_text_justify(JustifyText::Center)
         .with_style(bevy::ui::Style {
              position_type: PositionType::Absolute,
@@ -372,10 +604,10 @@ fn apply_movement(
     mut snake_query: Query<(&mut MySnakeGame, &mut LastUpdate, &SnakeMovementController)>,
     mut tilemap_query: Query<(&mut TileStorage, Entity)>,
     mut tile_texture_query: Query<&mut TileTextureIndex>,
+    mut tick_cadence: ResMut<TickCadence>,
 ) {
     for (mut my_snake_game, mut last_update, movement) in snake_query.iter_mut() {
-        if movement.is_paused { continue; } 
-        if let Some(dir) = movement.player_movement_intent {
+        if let Some(dir) = movement.step_direction(movement.player_movement_intent) {
             let current_time = time.elapsed_seconds_f64();
             if current_time - last_update.0 > 0.1 {
                 let prev_apples_eaten = my_snake_game.snake_game.apples_eaten;
@@ -395,7 +627,7 @@ fn apply_movement(
                     commands.trigger(PlaySfx::Key(SfxKey::Eating(0)));
                 } else if prev_snake_len != my_snake_game.snake_game.snake.locations.len() {
                     commands.trigger(PlaySfx::Key(SfxKey::Growing(0)));
-                } else if my_snake_game.snake_game.state != GameState::GameOver {
+                } else if my_snake_game.snake_game.state != GameState::GameOver && tick_cadence.should_tick() {
                     commands.trigger(PlaySfx::Key(SfxKey::Tick(0)));
                 }
                 last_update.0 = current_time;
@@ -405,6 +637,13 @@ fn apply_movement(
 }
 
 
+/// Where the tilemap last drew the tail and apple; `is_zero()` means nothing has been drawn there.
+#[derive(Copy, Clone, PartialEq, Debug)]
+struct DrawnLocations {
+    tail:  snake_game::GridPoint,
+    apple: snake_game::GridPoint,
+}
+
 fn update_tilemap(
     commands: &mut Commands,
     my_snake_game: &mut Mut<MySnakeGame>,
@@ -412,12 +651,21 @@ fn update_tilemap(
     mut tile_storage: Mut<TileStorage>,
     tile_texture_query: &mut Query<&mut TileTextureIndex>,
 ) {
-    let pt_tail_prev = my_snake_game.location_tail_prev;
-    let pt_apple_prev = my_snake_game.location_apple_prev;
-    let snake_game = &mut my_snake_game.snake_game;
+    let drawn = DrawnLocations { tail: my_snake_game.location_tail_prev, apple: my_snake_game.location_apple_prev };
+    let snake_game = &my_snake_game.snake_game;
+    for (pt, tile_texture_index) in plan_tile_updates(drawn, snake_game) {
+        update_tilemap_at_point(pt, tile_texture_index, commands, tilemap_entity, &mut tile_storage, tile_texture_query);
+    }
+    let (pt_tail, pt_apple) = (snake_game.snake.locations[snake_game.snake.locations.len() - 1], snake_game.apple.location);
+    my_snake_game.location_tail_prev = pt_tail;
+    my_snake_game.location_apple_prev = pt_apple;
+}
 
+/// The tiles to change, in order (a later entry for the same point wins), to take the tilemap from
+/// showing `drawn` to showing `snake_game` after one move.  `None` erases the tile.
+fn plan_tile_updates(drawn: DrawnLocations, snake_game: &snake_game::SnakeGame) -> Vec<(snake_game::GridPoint, Option<u32>)> {
+    let mut updates = Vec::with_capacity(6);
     let is_game_over = snake_game.state == snake_game::GameState::GameOver;
-    //if is_game_over { return; }   // We *could*n short-circuit updating, but only if we track whether we've already updated once after a game over
     
     // We don't need to update the *entire* map, just the locations where things might have 
     // changed (see comments on SnakeGame::move_snake() for details):
@@ -446,7 +694,7 @@ fn update_tilemap(
         let dir_head = dir_of_offset(pt_head - pt_head_prev);
         tile_texture_index_of_head_and_direction(dir_head)
     };
-    update_tilemap_at_point(pt_head, Some(tile_texture_index_head), commands, tilemap_entity, &mut tile_storage, tile_texture_query);
+    updates.push((pt_head, Some(tile_texture_index_head)));
 
     // Previous head (only if snake is longer than 2)
     let has_prev_head_that_is_not_tail = len > 2;
@@ -455,37 +703,36 @@ fn update_tilemap(
         let dir_head = dir_of_offset(pt_head - pt_head_prev);
         let dir_head_prev = dir_of_offset(pt_head_prev - pt_head_prev_prev);
         let tile_texture_index_head_prev = tile_texture_index_of_prev_and_next_directions(dir_head_prev, dir_head);
-        update_tilemap_at_point(pt_head_prev, Some(tile_texture_index_head_prev), commands, tilemap_entity, &mut tile_storage, tile_texture_query);
+        updates.push((pt_head_prev, Some(tile_texture_index_head_prev)));
     }
 
     // Snake Tail
-    let has_tail_moved = pt_tail_prev != pt_tail;
+    let has_tail_moved = drawn.tail != pt_tail;
     if has_tail_moved {
         // Erase old tail, unless the snake head replaces it (in which it's already been placed in tilemap)
-        let has_head_replaced_tail = pt_head == pt_tail_prev;
-        if !pt_tail_prev.is_zero() && !has_head_replaced_tail {
-            update_tilemap_at_point(pt_tail_prev, None, commands, tilemap_entity, &mut tile_storage, tile_texture_query);
+        let has_head_replaced_tail = pt_head == drawn.tail;
+        if !drawn.tail.is_zero() && !has_head_replaced_tail {
+            updates.push((drawn.tail, None));
         }
 
         // Now update the tile for the new tail
         let dir_tail = dir_of_offset(pt_almost_tail - pt_tail);
         let tile_texture_index_tail = tile_texture_index_of_tail_and_direction(dir_tail);
-        update_tilemap_at_point(pt_tail, Some(tile_texture_index_tail), commands, tilemap_entity, &mut tile_storage, tile_texture_query);
-        my_snake_game.location_tail_prev = pt_tail;
+        updates.push((pt_tail, Some(tile_texture_index_tail)));
     }
 
     // Apple
-    let has_apple_moved = pt_apple_prev != pt_apple;
+    let has_apple_moved = drawn.apple != pt_apple;
     if has_apple_moved {
         // Erase old apple unless eaten by snake (in which case the tile has already been covered by snake head)
-        let was_old_apple_eaten = !pt_apple_prev.is_zero() && pt_apple_prev == pt_head;
+        let was_old_apple_eaten = !drawn.apple.is_zero() && drawn.apple == pt_head;
         if !was_old_apple_eaten {
-            update_tilemap_at_point(pt_apple_prev, None, commands, tilemap_entity, &mut tile_storage, tile_texture_query);
+            updates.push((drawn.apple, None));
         }
-        // Draw new apple
-        update_tilemap_at_point(pt_apple, Some(TILE_APPLE), commands, tilemap_entity, &mut tile_storage, tile_texture_query);
-        my_snake_game.location_apple_prev = pt_apple;
+        // Draw new apple (after erasing the old tail, which it may have just replaced)
+        updates.push((pt_apple, Some(TILE_APPLE)));
     }
+    updates
 }
 
 fn update_tilemap_at_point(
@@ -529,3 +776,134 @@ fn update_tilemap_at_point(
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::snake_game::PlaybackEvents;
+
+    use super::*;
+
+    #[test]
+    fn test_daily_seed_is_deterministic_per_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 7, 4).unwrap();
+        let next_day = date.succ_opt().unwrap();
+        assert_eq!(daily_seed(date), daily_seed(NaiveDate::from_ymd_opt(2024, 7, 4).unwrap()));
+        assert_ne!(daily_seed(date), daily_seed(next_day));
+        let daily_challenge = DailyChallenge::for_date(date);
+        assert_eq!(daily_challenge, DailyChallenge::for_date(date));
+        assert!(daily_challenge.label().contains("2024-07-04"));
+        // The same seed means the same board, and the same apples
+        let (a, b) = (snake_game::SnakeGame::from_seed(daily_challenge.seed), snake_game::SnakeGame::from_seed(daily_seed(date)));
+        assert_eq!(a.playback.playback_events, b.playback.playback_events);
+    }
+
+    #[test]
+    fn test_pause_stops_every_intent() {
+        let mut controller = SnakeMovementController { player_movement_intent: Some(Dir::Left), is_paused: false };
+        assert!(controller.step_direction(controller.player_movement_intent) == Some(Dir::Left));
+        assert!(controller.step_direction(Some(Dir::Up)) == Some(Dir::Up));
+        controller.is_paused = true;
+        assert!(controller.step_direction(controller.player_movement_intent).is_none());
+        assert!(controller.step_direction(Some(Dir::Up)).is_none());
+    }
+
+    #[test]
+    fn test_tick_cadence() {
+        let mut every_third = TickCadence { every: 3, ..default() };
+        let ticks = (0..7).map(|_| every_third.should_tick()).collect::<Vec<_>>();
+        assert_eq!(vec![false, false, true, false, false, true, false], ticks);
+        let mut always = TickCadence::default();
+        assert!((0..3).all(|_| always.should_tick()));
+        let mut never = TickCadence { every: 0, ..default() };
+        assert!((0..3).all(|_| !never.should_tick()));
+    }
+
+    #[test]
+    fn test_default_bindings() {
+        let bindings = KeyBindings::default();
+        let (intent, is_new) = bindings.movement_intent(|k| k == KeyCode::ArrowLeft, |_| false);
+        assert!(intent == Some(Dir::Left) && !is_new);
+        let (intent, is_new) = bindings.movement_intent(|_| false, |_| false);
+        assert!(intent.is_none() && !is_new);
+        assert!(bindings.is_action(Action::Pause, |k| k == KeyCode::KeyP));
+    }
+
+    #[test]
+    fn test_custom_binding_for_up() {
+        let bindings = KeyBindings { bindings: vec![(KeyCode::KeyI, Action::Up), (KeyCode::KeyK, Action::Down)] };
+        let (intent, is_new) = bindings.movement_intent(|k| k == KeyCode::KeyI, |k| k == KeyCode::KeyI);
+        assert!(intent == Some(Dir::Up) && is_new);
+        // The default key for Up is no longer bound.
+        let (intent, _) = bindings.movement_intent(|k| k == KeyCode::KeyW, |_| false);
+        assert!(intent.is_none());
+    }
+
+    /// The tile `plan` leaves at `pt`: `None` if untouched, `Some(None)` if erased.
+    fn planned_tile_at(plan: &[(snake_game::GridPoint, Option<u32>)], pt: snake_game::GridPoint) -> Option<Option<u32>> {
+        plan.iter().rev().find(|&&(p, _)| p == pt).map(|&(_, tile)| tile)
+    }
+
+    fn game_at(head: snake_game::GridPoint, tail: snake_game::GridPoint, apple: snake_game::GridPoint) -> snake_game::SnakeGame {
+        snake_game::SnakeGame::from_playback(&[
+            PlaybackEvents::NewGame,
+            PlaybackEvents::NewSnakeLocation(head, tail),
+            PlaybackEvents::NewAppleLocation(apple),
+        ])
+    }
+
+    fn drawn_locations_of(game: &snake_game::SnakeGame) -> DrawnLocations {
+        DrawnLocations { tail: game.snake.locations[game.snake.locations.len() - 1], apple: game.apple.location }
+    }
+
+    #[test]
+    fn test_plan_new_apple_where_tail_just_was() {
+        let pt = snake_game::GridPoint::new;
+        let mut game = game_at(pt(5, 5), pt(4, 5), pt(6, 5));
+        let drawn = drawn_locations_of(&game);
+        // Eat the apple, and put the next one where the tail just moved from
+        game.move_snake(snake_game::Direction::East, Some(pt(4, 5)));
+        assert_eq!(1, game.apples_eaten);
+        let plan = plan_tile_updates(drawn, &game);
+        assert_eq!(Some(Some(TILE_APPLE)), planned_tile_at(&plan, pt(4, 5)));
+        assert_eq!(Some(Some(tile_texture_index_of_head_and_direction(Dir::Right))), planned_tile_at(&plan, pt(6, 5)));
+        assert_eq!(Some(Some(tile_texture_index_of_tail_and_direction(Dir::Right))), planned_tile_at(&plan, pt(5, 5)));
+    }
+
+    #[test]
+    fn test_plan_head_lands_on_old_tail() {
+        let pt = snake_game::GridPoint::new;
+        let mut game = game_at(pt(5, 5), pt(4, 5), pt(6, 5));
+        game.grow_increment = 2;
+        game.move_snake(snake_game::Direction::East, Some(pt(10, 10)));
+        game.move_snake(snake_game::Direction::North, None);
+        game.move_snake(snake_game::Direction::West, None);
+        assert_eq!(pt(5, 5), drawn_locations_of(&game).tail);
+        let drawn = drawn_locations_of(&game);
+        // Close the loop, chasing the tail into its old spot
+        game.move_snake(snake_game::Direction::South, None);
+        assert_eq!(GameState::Running, game.state);
+        let plan = plan_tile_updates(drawn, &game);
+        assert!(!plan.contains(&(pt(5, 5), None)), "{plan:?}");
+        assert_eq!(Some(Some(tile_texture_index_of_head_and_direction(Dir::Down))), planned_tile_at(&plan, pt(5, 5)));
+        assert_eq!(Some(Some(tile_texture_index_of_tail_and_direction(Dir::Up))), planned_tile_at(&plan, pt(6, 5)));
+        assert_eq!(None, planned_tile_at(&plan, pt(10, 10)));
+    }
+
+    #[test]
+    fn test_saved_playback_round_trips() {
+        let mut game = snake_game::SnakeGame::new(None);
+        while game.state == GameState::Running {
+            game.move_snake(snake_game::Direction::North, None);
+        }
+        let filename = std::env::temp_dir().join(format!("snake-bevy-test-{}-Playback.json", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        write_playback(&game.playback, filename).unwrap();
+        let loaded = LoadedPlayback::from_file(filename).unwrap();
+        fs::remove_file(filename).unwrap();
+        assert_eq!(game.playback.playback_events, loaded.0.playback_events);
+        let replayed = snake_game::SnakeGame::from_playback(&loaded.0.playback_events);
+        assert_eq!(game.snake.locations, replayed.snake.locations);
+        assert_eq!(GameState::GameOver, replayed.state);
+    }
+}
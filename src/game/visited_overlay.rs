@@ -0,0 +1,108 @@
+//! An optional overlay (toggled with V) tinting the cells the snake has visited since it last ate,
+//! i.e. `SnakeGame::visited_vector`, to show how a player (or net) explores the board.
+
+use bevy::prelude::*;
+
+use crate::screen::Screen;
+use crate::snake_game::{Grid, GridPoint, SnakeGame};
+use crate::AppSet;
+use crate::TILE_SIZE;
+
+use super::snake_visualizer::Action;
+use super::snake_visualizer::KeyBindings;
+use super::snake_visualizer::MySnakeGame;
+
+const VISITED_TINT: Color = Color::srgba(1.0, 0.85, 0.2, 0.25);
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<VisitedOverlay>();
+    app.add_systems(Update, record_overlay_toggle.in_set(AppSet::RecordInput));
+    app.add_systems(Update, draw_visited_overlay.in_set(AppSet::Update));
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub(super) struct VisitedOverlay {
+    is_shown: bool,
+}
+
+/// One tinted square of the overlay.
+#[derive(Component)]
+pub(super) struct VisitedOverlayTile;
+
+
+/// The cells `game` has marked as visited.
+fn visited_points(game: &SnakeGame) -> Vec<GridPoint> {
+    game.visited_vector.iter().enumerate()
+        .filter(|&(_, &is_visited)| is_visited)
+        .map(|(i, _)| GridPoint::new((i % Grid::WIDTH as usize) as i16, (i / Grid::WIDTH as usize) as i16))
+        .collect()
+}
+
+/// World position of the center of cell `pt`, matching the tilemap, which is centered on the origin.
+fn cell_center(pt: GridPoint, grid_width: i16, grid_height: i16) -> Vec2 {
+    let offset = Vec2::new(pt.x as f32 - (grid_width - 1) as f32 / 2.0, pt.y as f32 - (grid_height - 1) as f32 / 2.0);
+    offset * TILE_SIZE
+}
+
+
+fn record_overlay_toggle(
+    input: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut overlay: ResMut<VisitedOverlay>,
+) {
+    if key_bindings.is_action(Action::ToggleVisitedOverlay, |k| input.just_pressed(k)) {
+        overlay.is_shown = !overlay.is_shown;
+    }
+}
+
+/// Respawns the overlay whenever it's toggled or the game changes.
+fn draw_visited_overlay(
+    mut commands: Commands,
+    overlay: Res<VisitedOverlay>,
+    snake_query: Query<Ref<MySnakeGame>>,
+    overlay_tile_query: Query<Entity, With<VisitedOverlayTile>>,
+) {
+    let Ok(my_snake_game) = snake_query.get_single() else { return; };
+    if !overlay.is_changed() && !my_snake_game.is_changed() { return; }
+    for entity in &overlay_tile_query {
+        commands.entity(entity).despawn();
+    }
+    if !overlay.is_shown { return; }
+
+    let snake_game = &my_snake_game.snake_game;
+    for pt in visited_points(snake_game) {
+        commands.spawn((
+            Name::new("Visited"),
+            SpriteBundle {
+                sprite: Sprite { color: VISITED_TINT, custom_size: Some(TILE_SIZE), ..default() },
+                transform: Transform::from_translation(cell_center(pt, snake_game.grid.width, snake_game.grid.height).extend(1.0)),
+                ..default()
+            },
+            VisitedOverlayTile,
+            StateScoped(Screen::Playing),
+        ));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::snake_game::Direction;
+
+    use super::*;
+
+    #[test]
+    fn test_visited_points_and_their_positions() {
+        let mut game = SnakeGame::new(None);
+        game.clear_visited();
+        assert!(visited_points(&game).is_empty());
+        let head = game.snake.head_location;
+        let dir = if head.x < Grid::WIDTH / 2 { Direction::East } else { Direction::West };
+        game.move_snake(dir, None);
+        assert_eq!(vec![game.snake.head_location], visited_points(&game));
+
+        assert_eq!(Vec2::new(-1.5, -0.5) * TILE_SIZE, cell_center(GridPoint::new(0, 0), 4, 2));
+        assert_eq!(Vec2::new( 1.5,  0.5) * TILE_SIZE, cell_center(GridPoint::new(3, 1), 4, 2));
+    }
+}
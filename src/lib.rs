@@ -7,16 +7,66 @@ mod snake_game;
 mod neural_net;
 pub mod nn_plays_snake;
 pub mod cmdline;
+pub mod sim_logger;
+
+pub use game::playback_viewer::LoadedPlayback;
+pub use game::snake_visualizer::DailyChallenge;
+pub use snake_game::Playback;
 
 use bevy::{
-    asset::AssetMetaCheck, audio::{AudioPlugin, Volume}, prelude::*, render::camera::ScalingMode, window::WindowResolution
+    asset::AssetMetaCheck, audio::{AudioPlugin, Volume}, prelude::*, render::camera::ScalingMode, window::{WindowResizeConstraints, WindowResolution}
 };
 
-// TODO: Base these off of the snake game size?
-pub const WINDOW_SIZE_X: f32 = 40.0 * 16.0 + 40.0;
-pub const WINDOW_SIZE_Y: f32 = 30.0 * 16.0 + 40.0;
+/// On-screen size, in pixels, of one grid cell.  Tiles needn't be square.
+pub const TILE_SIZE: Vec2 = Vec2::new(16.0, 16.0);
+/// Space, in pixels, between the board and the window's edges (split evenly on both sides).
+pub const WINDOW_MARGIN: f32 = 40.0;
+
+/// Size, in pixels, of a `grid_width` x `grid_height` board.
+pub fn board_size(grid_width: i16, grid_height: i16) -> Vec2 {
+    Vec2::new(grid_width as f32, grid_height as f32) * TILE_SIZE
+}
+
+/// Window size that shows a whole `grid_width` x `grid_height` board plus its margin.
+pub fn window_size(grid_width: i16, grid_height: i16) -> Vec2 {
+    board_size(grid_width, grid_height) + Vec2::splat(WINDOW_MARGIN)
+}
+
+/// Window pixels per world unit (i.e. per `TILE_SIZE` pixel) so that no tile renders smaller than
+/// `min_tile_pixels` on either axis.  Never below 1.0: tiles are never drawn smaller than `TILE_SIZE`.
+fn pixel_scale(min_tile_pixels: f32) -> f32 {
+    (min_tile_pixels / TILE_SIZE.min_element()).max(1.0)
+}
+
+/// Window size, in pixels, that shows a whole `grid_width` x `grid_height` board plus its margin
+/// with tiles at least `min_tile_pixels` on a side.  Also the smallest the window may be resized to.
+pub fn min_window_size(grid_width: i16, grid_height: i16, min_tile_pixels: f32) -> Vec2 {
+    window_size(grid_width, grid_height) * pixel_scale(min_tile_pixels)
+}
+
+/// World-space area the camera shows in a `window`-sized window when it must show all of
+/// `content`: `content` scaled up along one axis to the window's aspect ratio, i.e. letterboxed.
+/// The tilemap is centered on the origin, and so is this.
+fn camera_area(content: Vec2, window: Vec2) -> Rect {
+    let scale = (content / window).max_element();
+    Rect::from_center_size(Vec2::ZERO, window * scale)
+}
+
+pub struct AppPlugin {
+    /// Smallest on-screen size, in pixels, of a grid cell; the window is sized (and can't be resized
+    /// smaller) to honor it, and a larger window letterboxes the board.
+    pub min_tile_pixels: f32,
+}
+
+impl Default for AppPlugin {
+    fn default() -> Self {
+        Self { min_tile_pixels: TILE_SIZE.min_element() }
+    }
+}
 
-pub struct AppPlugin;
+/// The window's size when created, in pixels, for `spawn_camera()` to fit the board into.
+#[derive(Resource, Clone, Copy, Debug)]
+struct InitialWindowSize(Vec2);
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
@@ -26,7 +76,10 @@ impl Plugin for AppPlugin {
             (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
         );
 
+        let window_size = min_window_size(snake_game::Grid::WIDTH, snake_game::Grid::HEIGHT, self.min_tile_pixels);
+
         // Spawn the main camera.
+        app.insert_resource(InitialWindowSize(window_size));
         app.add_systems(Startup, spawn_camera);
 
         // Add Bevy plugins.
@@ -45,7 +98,8 @@ impl Plugin for AppPlugin {
                         canvas: Some("#bevy".to_string()),
                         fit_canvas_to_parent: true,
                         prevent_default_event_handling: true,
-                        resolution: WindowResolution::new(WINDOW_SIZE_X, WINDOW_SIZE_Y).with_scale_factor_override(1.0),
+                        resolution: WindowResolution::new(window_size.x, window_size.y).with_scale_factor_override(1.0),
+                        resize_constraints: WindowResizeConstraints { min_width: window_size.x, min_height: window_size.y, ..default() },
                         ..default()
                     }
                     .into(),
@@ -81,12 +135,15 @@ enum AppSet {
     Update,
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(mut commands: Commands, initial_window_size: Res<InitialWindowSize>) {
     let mut camera = Camera2dBundle::default();
 
-    // Automatically change camera based on size of containing window:
-    camera.projection.scaling_mode = ScalingMode::FixedVertical(WINDOW_SIZE_Y);
-    camera.projection.area = Rect::new(0.0, 0.0, WINDOW_SIZE_X, WINDOW_SIZE_Y);
+    // Automatically change camera based on size of containing window, always showing the whole
+    // board whatever the window's aspect ratio.  AutoMin letterboxes just as `camera_area()` does,
+    // and scales the board up to fill the (at least min_window_size()) window:
+    let content_size = window_size(snake_game::Grid::WIDTH, snake_game::Grid::HEIGHT);
+    camera.projection.scaling_mode = ScalingMode::AutoMin { min_width: content_size.x, min_height: content_size.y };
+    camera.projection.area = camera_area(content_size, initial_window_size.0);
     
     commands.spawn((
         Name::new("Camera"),
@@ -100,3 +157,47 @@ fn spawn_camera(mut commands: Commands) {
         IsDefaultUiCamera,
     ));
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_fits_rectangular_board_centered() {
+        assert_eq!(Vec2::new(60.0 * 16.0 + 40.0, 40.0 * 16.0 + 40.0), window_size(60, 40));
+        let board = Rect::from_center_size(Vec2::ZERO, board_size(60, 40));
+        let area = camera_area(window_size(60, 40), window_size(60, 40));
+        assert!(area.contains(board.min) && area.contains(board.max));
+        assert_eq!(Vec2::splat(WINDOW_MARGIN / 2.0), board.min - area.min);
+        assert_eq!(Vec2::splat(WINDOW_MARGIN / 2.0), area.max - board.max);
+    }
+
+    #[test]
+    fn test_min_window_size_honors_min_tile_pixels() {
+        assert_eq!(window_size(40, 30), min_window_size(40, 30, TILE_SIZE.x));
+        // Tiles are never shrunk below TILE_SIZE
+        assert_eq!(window_size(40, 30), min_window_size(40, 30, 4.0));
+        let window = min_window_size(40, 30, 24.0);
+        assert_eq!(window_size(40, 30) * 1.5, window);
+        // In a window that size, the camera shows exactly the board plus margin: 24-pixel tiles
+        let area = camera_area(window_size(40, 30), window);
+        assert!(((window / area.size()) * TILE_SIZE - Vec2::splat(24.0)).abs().max_element() < 1e-3);
+    }
+
+    #[test]
+    fn test_letterboxed_board_fully_visible() {
+        let window = Vec2::new(800.0, 600.0);
+        for (grid_width, grid_height) in [(80, 20), (20, 60)] {
+            let content = window_size(grid_width, grid_height);
+            let board = Rect::from_center_size(Vec2::ZERO, board_size(grid_width, grid_height));
+            let area = camera_area(content, window);
+            assert!(area.contains(board.min) && area.contains(board.max), "{grid_width}x{grid_height}");
+            assert_eq!(board.center(), area.center());
+            assert!(((area.width() / area.height()) - (window.x / window.y)).abs() < 1e-5);
+            // Letterboxed along exactly one axis
+            let slack = area.size() - content;
+            assert!(slack.min_element().abs() < 1e-3 && slack.max_element() > 0.0, "{slack}");
+        }
+    }
+}
@@ -1,19 +1,88 @@
 // Disable console on Windows for non-dev builds.
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
+use std::path::{Path, PathBuf};
+
 use bevy::prelude::*;
 use clap::Parser;
+use snake_bevy::cmdline::{Args, Command, TrainArgs};
 use snake_bevy::nn_plays_snake::NnPlaysSnake;
-use snake_bevy::{cmdline::Args, AppPlugin};
+use snake_bevy::{sim_logger, AppPlugin, DailyChallenge, LoadedPlayback, Playback};
 
 
 fn main() -> AppExit {
     let args = Args::parse();
-    if args.sim {
-        let mut nn_player = NnPlaysSnake::new();
-        nn_player.run_x_generations();
-        AppExit::Success
-    } else {
-        App::new().add_plugins(AppPlugin).run()
+    let app_plugin = match args.min_tile_pixels {
+        Some(min_tile_pixels) => AppPlugin { min_tile_pixels },
+        None => AppPlugin::default(),
+    };
+    match args.command.unwrap_or(Command::Play) {
+        Command::Play => run_game(app_plugin, None, None),
+        Command::Daily => run_game(app_plugin, None, Some(DailyChallenge::today())),
+        Command::Watch { playback } => match LoadedPlayback::from_file(&playback) {
+            Ok(loaded_playback) => run_game(app_plugin, Some(loaded_playback), None),
+            Err(e) => { eprintln!("ERROR loading playback: {e}"); AppExit::error() }
+        },
+        Command::Inspect { net, playback } => match NnPlaysSnake::inspect_net(&net, playback.as_deref()) {
+            Ok(report) => { print!("{report}"); AppExit::Success }
+            Err(e) => { eprintln!("ERROR inspecting net: {e}"); AppExit::error() }
+        },
+        Command::Bench { net, iterations } => match NnPlaysSnake::bench_net(&net, iterations) {
+            Ok(report) => { print!("{report}"); AppExit::Success }
+            Err(e) => { eprintln!("ERROR benchmarking net: {e}"); AppExit::error() }
+        },
+        Command::DiffPlayback { a, b } => match (Playback::from_file(&a), Playback::from_file(&b)) {
+            (Ok(a), Ok(b)) => { print!("{}", a.diff(&b)); AppExit::Success }
+            (Err(e), _) | (_, Err(e)) => { eprintln!("ERROR loading playback: {e}"); AppExit::error() }
+        },
+        Command::Train(train_args) => train(train_args),
+    }
+}
+
+/// Launches the interactive game (optionally as a daily challenge), or, given a loaded playback,
+/// the viewer for it.
+fn run_game(app_plugin: AppPlugin, loaded_playback: Option<LoadedPlayback>, daily_challenge: Option<DailyChallenge>) -> AppExit {
+    let mut app = App::new();
+    app.add_plugins(app_plugin);
+    if let Some(daily_challenge) = daily_challenge {
+        app.insert_resource(daily_challenge);
+    }
+    if let Some(loaded_playback) = loaded_playback {
+        app.insert_resource(loaded_playback);
+    }
+    app.run()
+}
+
+fn train(args: TrainArgs) -> AppExit {
+    sim_logger::init(args.verbosity());
+    let mut nn_player = NnPlaysSnake::new();
+    #[cfg(feature = "progress")]
+    nn_player.set_show_progress(!args.quiet);
+    nn_player.set_dump_fitness_dir(args.dump_fitness.map(PathBuf::from));
+    if let Err(e) = nn_player.set_metrics_jsonl(args.metrics.as_deref().map(Path::new)) {
+        eprintln!("ERROR opening metrics file: {e}");
+        return AppExit::error();
+    }
+    nn_player.set_champion_snapshots(args.snapshots);
+    nn_player.set_champion_traces(args.traces);
+    if let Some(path) = &args.resume {
+        if let Err(e) = nn_player.resume_from_checkpoint(path) {
+            eprintln!("ERROR resuming: {e}");
+            return AppExit::error();
+        }
+    }
+    #[cfg(feature = "graceful_shutdown")]
+    {
+        let stop_requested = nn_player.stop_requested();
+        // The first Ctrl-C stops after the current generation; a second one doesn't wait
+        let on_ctrl_c = move || if stop_requested.swap(true, std::sync::atomic::Ordering::SeqCst) { std::process::exit(130); };
+        if let Err(e) = ctrlc::set_handler(on_ctrl_c) { eprintln!("ERROR installing Ctrl-C handler: {e}"); }
+    }
+    if let Some(decimals) = args.weight_decimals {
+        nn_player.set_champion_weight_decimals(decimals);
+    }
+    match nn_player.run_x_generations() {
+        Ok(summary) => { print!("{summary}"); AppExit::Success }
+        Err(e) => { eprintln!("ERROR: {e}"); AppExit::error() }
     }
 }
@@ -1,8 +1,9 @@
-use rand::{thread_rng, Rng};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum ActivationFunction {
     None,       // f(x) = x, i.e. Linear
     Sigmoid,    // f(x) = 1.0 / (1.0 + exp(-x));                                f(4) = 0.982013790037908
@@ -38,8 +39,8 @@ impl ActivationFunction {
         }
     }
 
-    pub fn choose_random() -> Self {
-        match thread_rng().gen_range(0..5) {
+    pub fn choose_random(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..5) {
             0 => ActivationFunction::None,
             1 => ActivationFunction::Sigmoid,
             2 => ActivationFunction::ReLU,
@@ -48,17 +49,31 @@ impl ActivationFunction {
             _ => panic!("Unexpected choice for choose_random()")
         }
     }
+
+    /// Uniformly picks one of `allowed`, which must not be empty.
+    pub fn choose_random_from(allowed: &[Self], rng: &mut impl Rng) -> Self {
+        allowed[rng.gen_range(0..allowed.len())]
+    }
+
+    /// Picks one of `allowed` with probability proportional to its weight.  The weights must not
+    /// be negative, and at least one must be positive.
+    pub fn choose_random_weighted(allowed: &[(Self, f64)], rng: &mut impl Rng) -> Self {
+        let weighted_index = WeightedIndex::new(allowed.iter().map(|&(_, weight)| weight)).expect("Bad activation function weights");
+        allowed[weighted_index.sample(rng)].0
+    }
 }
 
 
 
 #[cfg(test)]
 mod tests {
+    use rand::thread_rng;
+
     use super::ActivationFunction;
 
     #[test]
     fn test_funtions() {
-        for (i, &x) in [-2.0, 1.0, 0.0, 123.456, -3.1415926, -0.000001, 4.0].iter().enumerate() {
+        for (i, &x) in [-2.0, 1.0, 0.0, 123.456, -std::f32::consts::PI, -0.000001, 4.0].iter().enumerate() {
             assert_eq!( x, ActivationFunction::linear( x));
             assert_eq!(-x, ActivationFunction::linear(-x));
 
@@ -96,9 +111,10 @@ mod tests {
 
     #[test]
     fn test_choose() {
+        let mut rng = thread_rng();
         let mut found = [false; 5];
         for _ in 0..1000 {
-            let i = match ActivationFunction::choose_random() {
+            let i = match ActivationFunction::choose_random(&mut rng) {
                 ActivationFunction::None    => 0,
                 ActivationFunction::Sigmoid => 1,
                 ActivationFunction::ReLU    => 2,
@@ -109,4 +125,14 @@ mod tests {
         }
         assert!(found.iter().all(|&b| b));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_choose_from_allowed() {
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            assert_eq!(ActivationFunction::Tanh, ActivationFunction::choose_random_from(&[ActivationFunction::Tanh], &mut rng));
+            assert_eq!(ActivationFunction::ReLU, ActivationFunction::choose_random_weighted(&[(ActivationFunction::ReLU, 0.5)], &mut rng));
+            assert_eq!(ActivationFunction::ReLU, ActivationFunction::choose_random_weighted(&[(ActivationFunction::ReLU, 1.0), (ActivationFunction::None, 0.0)], &mut rng));
+        }
+    }
+}
@@ -14,6 +14,16 @@ impl ConnectionId {
     pub fn new_unique() -> ConnectionId {
         ConnectionId(CONNECTION_ID_NEXT.fetch_add(1, Ordering::SeqCst))
     }
+
+    /// The number the next `new_unique()` will use.
+    pub(super) fn next_unused() -> usize {
+        CONNECTION_ID_NEXT.load(Ordering::SeqCst)
+    }
+
+    /// Makes `new_unique()` hand out `next` or later from now on; never moves backward.
+    pub(super) fn reserve_before(next: usize) {
+        CONNECTION_ID_NEXT.fetch_max(next, Ordering::SeqCst);
+    }
 }
 
 impl fmt::Display for ConnectionId {
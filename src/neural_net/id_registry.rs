@@ -0,0 +1,64 @@
+//! Node and connection ids are what line genes up in crossover, and they're handed out from
+//! global counters that start over with each run.  Saving an `IdRegistry` with a population and
+//! restoring it on resume keeps new mutations from reusing the ids of the saved nets' genes.
+
+use serde::{Deserialize, Serialize};
+
+use super::connections::ConnectionId;
+use super::nets::NetId;
+use super::nodes::NodeId;
+
+
+/// The next NetId, NodeId, and ConnectionId to be handed out.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct IdRegistry {
+    pub net_id_next:        usize,
+    pub node_id_next:       usize,
+    pub connection_id_next: usize,
+}
+
+impl IdRegistry {
+    pub fn current() -> Self {
+        IdRegistry {
+            net_id_next:        NetId::next_unused(),
+            node_id_next:       NodeId::next_unused(),
+            connection_id_next: ConnectionId::next_unused(),
+        }
+    }
+
+    /// Continues handing out ids from where `self` left off, unless this run is already further
+    /// along (ids never go backward, so restoring can't create duplicates).
+    pub fn restore(&self) {
+        NetId::reserve_before(self.net_id_next);
+        NodeId::reserve_before(self.node_id_next);
+        ConnectionId::reserve_before(self.connection_id_next);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_continue_after_restore() {
+        let now = IdRegistry::current();
+        let saved = IdRegistry {
+            net_id_next:        now.net_id_next + 1_000,
+            node_id_next:       now.node_id_next + 1_000,
+            connection_id_next: now.connection_id_next + 1_000,
+        };
+        let loaded: IdRegistry = serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        assert_eq!(saved, loaded);
+        loaded.restore();
+        // Other tests may be creating ids concurrently, so only a lower bound is certain
+        let after = IdRegistry::current();
+        assert!(after.net_id_next >= saved.net_id_next && after.node_id_next >= saved.node_id_next && after.connection_id_next >= saved.connection_id_next);
+        NodeId::new_unique();
+        assert!(IdRegistry::current().node_id_next > saved.node_id_next);
+
+        // Restoring an older registry doesn't move the counters backward
+        now.restore();
+        assert!(IdRegistry::current().node_id_next >= saved.node_id_next);
+    }
+}
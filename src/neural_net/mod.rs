@@ -1,8 +1,9 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
-mod activation_functions;
-mod layers;
+pub mod activation_functions;
+pub mod id_registry;
+pub mod layers;
 mod nodes;
 mod connections;
 pub mod nets;
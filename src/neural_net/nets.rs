@@ -1,8 +1,8 @@
 
-use std::{fmt, sync::atomic::{AtomicUsize, Ordering}};
+use std::{collections::BTreeMap, fmt, sync::atomic::{AtomicUsize, Ordering}};
 use bevy::utils::hashbrown::{HashMap, HashSet};
-use log::{debug, trace};
-use rand::{thread_rng, Rng, prelude::SliceRandom};
+use log::{debug, trace, warn};
+use rand::{Rng, prelude::SliceRandom};
 use serde::{Deserialize, Serialize};
 
 use super::{activation_functions::ActivationFunction, connections::{Connection, ConnectionId}, layers::Layer, nodes::{Node, NodeId}, populations::FitnessInfo};
@@ -20,13 +20,23 @@ static NET_ID_NEXT: AtomicUsize = AtomicUsize::new(1);
 
 /// The NetId uniquely identifies an instance of a Net.  Used for debug checks to ensure node and
 /// connection indexes can only be used for the Net that generated them.
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct NetId(pub usize);
 
 impl NetId {
     pub fn new_unique() -> NetId {
         NetId(NET_ID_NEXT.fetch_add(1, Ordering::SeqCst))
     }
+
+    /// The number the next `new_unique()` will use.
+    pub(super) fn next_unused() -> usize {
+        NET_ID_NEXT.load(Ordering::SeqCst)
+    }
+
+    /// Makes `new_unique()` hand out `next` or later from now on; never moves backward.
+    pub(super) fn reserve_before(next: usize) {
+        NET_ID_NEXT.fetch_max(next, Ordering::SeqCst);
+    }
 }
 
 impl fmt::Display for NetId {
@@ -99,6 +109,7 @@ impl NetParams {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MutationParams {
     pub prob_mutate_activation_function_of_node: f64,
+    pub activation_function_weights: Vec<(ActivationFunction, f64)>,  // Empty for all, uniformly
     pub prob_mutate_weight: f64,
     pub prob_reset_weight_when_mutating: f64,
     pub max_weight_change_frac: f32,
@@ -108,6 +119,80 @@ pub struct MutationParams {
     pub prob_add_connection: f64,
     pub prob_remove_node: f64,
     pub prob_add_node: f64,
+    pub max_hidden_depth: Option<u16>,  // None for unlimited; else adding a node never makes a Layer::Hidden(n) with n > this
+    pub structural_mutations_per_call: usize,   // Rounds of add-connection/add-node per mutate_self(); >1 for "burst" mode
+}
+
+impl MutationParams {
+    /// The usual regime: mostly weight tweaks, with occasional structural growth.
+    pub fn neat_default() -> Self {
+        MutationParams {
+            prob_mutate_activation_function_of_node: 0.02,
+            activation_function_weights: Vec::new(),
+            prob_mutate_weight: 0.80,
+            prob_reset_weight_when_mutating: 0.10,
+            max_weight_change_frac: 0.10,   // +/- 10% of current value
+            prob_toggle_enabled: 0.025,
+            prob_remove_connection: 0.0,
+            prob_add_connection: 0.05,
+            prob_remove_node: 0.0,
+            prob_add_node: 0.03,
+            max_hidden_depth: None,
+            structural_mutations_per_call: 1,
+        }
+    }
+
+    /// Bigger and more frequent changes, including pruning, e.g. to escape a stagnant population.
+    pub fn aggressive() -> Self {
+        MutationParams {
+            prob_mutate_activation_function_of_node: 0.10,
+            prob_mutate_weight: 0.90,
+            prob_reset_weight_when_mutating: 0.25,
+            max_weight_change_frac: 0.50,
+            prob_toggle_enabled: 0.05,
+            prob_remove_connection: 0.02,
+            prob_add_connection: 0.20,
+            prob_remove_node: 0.02,
+            prob_add_node: 0.10,
+            ..Self::neat_default()
+        }
+    }
+
+    /// Only tunes the weights of the existing topology.
+    pub fn weights_only() -> Self {
+        MutationParams {
+            prob_mutate_activation_function_of_node: 0.0,
+            prob_toggle_enabled: 0.0,
+            prob_add_connection: 0.0,
+            prob_add_node: 0.0,
+            ..Self::neat_default()
+        }
+    }
+}
+
+
+/// An exact topology to build with `Net::from_spec()`, e.g. for precise regression tests.  Nodes
+/// are referred to by their position in `nodes`, which must be the inputs, then the outputs, then
+/// any hidden nodes (i.e. the same order `Net::new()` uses).
+#[derive(Clone, Debug)]
+pub struct NetSpec {
+    pub net_params: NetParams,
+    pub nodes: Vec<NodeSpec>,
+    pub connections: Vec<ConnectionSpec>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct NodeSpec {
+    pub activation_function: ActivationFunction,
+    pub layer: Layer,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ConnectionSpec {
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub is_enabled: bool,
 }
 
 
@@ -126,6 +211,14 @@ pub struct Net<Fit> where Fit: FitnessInfo {
 }
 
 impl <Fit> Net<Fit> where Fit: FitnessInfo {
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn connection_count(&self) -> usize { self.connections.len() }
+
+    pub fn enabled_connection_count(&self) -> usize { self.connections.iter().filter(|c| c.is_enabled).count() }
+
+    pub fn hidden_node_count(&self) -> usize { self.nodes.iter().filter(|n| matches!(n.layer, Layer::Hidden(_))).count() }
+
     pub fn get_node(&self, i: NodeIndex) -> &Node {
         assert_eq!(i.0, self.id);
         &self.nodes[i.1]
@@ -143,6 +236,17 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         &mut self.connections[i.1]
     }
 
+    /// Like `get_node()`, but `None` for an index from another net or past the end, e.g. one held by a stale viewer.
+    pub fn try_get_node(&self, i: NodeIndex) -> Option<&Node> {
+        if i.0 != self.id { return None; }
+        self.nodes.get(i.1)
+    }
+    /// Like `get_connection()`, but `None` for an index from another net or past the end.
+    pub fn try_get_connection(&self, i: ConnectionIndex) -> Option<&Connection> {
+        if i.0 != self.id { return None; }
+        self.connections.get(i.1)
+    }
+
     fn add_node(&mut self, id: Option<NodeId>, activation_function: ActivationFunction, layer: Option<Layer>, value: f32) -> NodeIndex {
         let index = NodeIndex(self.id, self.nodes.len());
         let node = Node {
@@ -204,10 +308,93 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         net
     }
 
+    /// A new net given its first, random mutation, with all randomness from `rng`.
+    pub fn random_with(net_params: NetParams, mut_params: &MutationParams, rng: &mut impl Rng) -> Self {
+        let mut net = Self::new(net_params);
+        net.mutate_self(mut_params, 1.0, rng);
+        net
+    }
+
+    /// Builds exactly the net described by `spec`, or explains why the spec is invalid.
+    pub fn from_spec(spec: &NetSpec) -> Result<Self, String> {
+        let input_count  = spec.net_params.input_count;
+        let output_count = spec.net_params.output_count;
+        if spec.nodes.len() < input_count + output_count {
+            return Err(format!("{} nodes is too few for {input_count} inputs and {output_count} outputs", spec.nodes.len()));
+        }
+        for (i, node) in spec.nodes.iter().enumerate() {
+            let expected = if i < input_count { "Input" } else if i < input_count + output_count { "Output" } else { "Hidden" };
+            let is_ok = match node.layer {
+                Layer::Input  => expected == "Input",
+                Layer::Output => expected == "Output",
+                Layer::Hidden(_) | Layer::Unreachable => expected == "Hidden",
+            };
+            if !is_ok { return Err(format!("Node {i} is {}, but should be {expected}", node.layer)); }
+        }
+        for (i, c) in spec.connections.iter().enumerate() {
+            if c.from >= spec.nodes.len() || c.to >= spec.nodes.len() { return Err(format!("Connection {i} refers to a non-existent node")); }
+            if spec.nodes[c.to  ].layer == Layer::Input  { return Err(format!("Connection {i} leads into an input node")); }
+            if spec.nodes[c.from].layer == Layer::Output { return Err(format!("Connection {i} leads out of an output node")); }
+            if spec.connections[..i].iter().any(|other| other.from == c.from && other.to == c.to) {
+                return Err(format!("Connection {i} duplicates an earlier connection"));
+            }
+        }
+        if Self::spec_has_cycle(spec) { return Err("Connections contain a cycle".to_string()); }
+
+        let mut net = Self::new(spec.net_params.clone());
+        for (node_spec, node) in spec.nodes.iter().zip(net.nodes.iter_mut()) {
+            node.activation_function = node_spec.activation_function;
+        }
+        for node_spec in spec.nodes.iter().skip(input_count + output_count) {
+            net.add_node(None, node_spec.activation_function, Some(node_spec.layer), 0.0);
+        }
+        for c in spec.connections.iter() {
+            net.add_connection(None, c.weight, c.is_enabled, NodeIndex(net.id, c.from), NodeIndex(net.id, c.to));
+        }
+        net.build_evaluation_order();
+        net.verify_invariants();
+        Ok(net)
+    }
+
+    fn spec_has_cycle(spec: &NetSpec) -> bool {
+        let edges = spec.connections.iter().map(|c| (c.from, c.to)).collect::<Vec<_>>();
+        Self::has_cycle(spec.nodes.len(), &edges)
+    }
+
+    /// Whether the (from, to) `edges` between `node_count` nodes contain a cycle.
+    fn has_cycle(node_count: usize, edges: &[(usize, usize)]) -> bool {
+        // Depth first search, where a node that's still "in progress" when we reach it again means a cycle
+        #[derive(Copy, Clone, PartialEq)]
+        enum Visit { NotYet, InProgress, Done }
+        fn visit(edges: &[(usize, usize)], state: &mut [Visit], node: usize) -> bool {
+            match state[node] {
+                Visit::Done => return false,
+                Visit::InProgress => return true,
+                Visit::NotYet => {}
+            }
+            state[node] = Visit::InProgress;
+            if edges.iter().filter(|&&(_, to)| to == node).any(|&(from, _)| visit(edges, state, from)) { return true; }
+            state[node] = Visit::Done;
+            false
+        }
+        let mut state = vec![Visit::NotYet; node_count];
+        (0..node_count).any(|node| visit(edges, &mut state, node))
+    }
+
+    /// Whether the enabled connections are free of cycles, e.g. to check an imported net before
+    /// evaluating it, without `verify_invariants()`'s panics.
+    pub fn is_feed_forward(&self) -> bool {
+        let edges = self.connections.iter()
+            .filter(|c| c.is_enabled)
+            .map(|c| (c.input_node.1, c.output_node.1))
+            .collect::<Vec<_>>();
+        !Self::has_cycle(self.nodes.len(), &edges)
+    }
+
     // NOTE: If we recursively traverse the network *once*, we can build the order that the network
     // needs to be evaluated in!  Then, to evaluate, we simply linearly replay the eval list--no
     // recursion or "node_has_been_evaluated" logic needed!
-    fn build_evaluation_order(&mut self) {
+    pub(crate) fn build_evaluation_order(&mut self) {
         if self.is_evaluation_order_up_to_date { return; }
         let mut node_has_been_evaluated = vec![false; self.nodes.len()];
         let mut node_order_list = Vec::<NodeIndex>::with_capacity(self.nodes.len());
@@ -223,7 +410,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         // Figure out the order to compute nodes and what layer the nodes belong to.
         for node_index in self.nodes.iter().filter_map(|n| if n.layer == Layer::Output { Some(n.index) } else { None }) {
             self.build_evaluation_order_recurse(0, &mut node_order_list, &mut node_has_been_evaluated, node_index);
-            self.build_layer_order_recurse(0, &mut layer_list, node_index);
+            self.build_layer_order_recurse(0, &mut layer_list, node_index, None);
         }
 
         // Copy layer number to nodes.layer from layer_list[node_index], fixing the output layers,
@@ -234,8 +421,12 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
             match node.layer {
                 Layer::Input => assert!(is_none_or(layer, |&layer| layer == 0)),
                 Layer::Output => {
-                    debug!("assert: layer={layer:?}, conn_count={}", node.input_connections.len());
-                    assert!(is_none_or(layer, |&layer| layer > 0 || node.input_connections.is_empty()));
+                    // An output whose inputs all come from nodes nothing feeds (or only over
+                    // disabled connections) has no effective inputs; it just evaluates to a
+                    // constant, so note it rather than insist on its layer.
+                    if is_none_or(layer, |&layer| layer == 0) && !node.input_connections.is_empty() {
+                        debug!("Output {} has {} input connections, but no effective inputs", node.index, node.input_connections.len());
+                    }
                 },
                 _ => node.layer = if let Some(&layer) = layer {
                     //assert!(layer > 0);       // TODO: Re-enable?
@@ -276,9 +467,11 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         node_has_been_evaluated[node_index.1] = true;
     }
 
-    fn build_layer_order_recurse(&self, recursion: usize, layer_list: &mut HashMap<NodeIndex, u16>, node_index: NodeIndex) -> u16 {
+    /// Computes the layer of `node_index` (and of the nodes it depends on) into `layer_list`.  With
+    /// `split`, computes the layers as if that connection had been `split_connection()`ed.
+    fn build_layer_order_recurse(&self, recursion: usize, layer_list: &mut HashMap<NodeIndex, u16>, node_index: NodeIndex, split: Option<ConnectionIndex>) -> u16 {
         if recursion > 2 * self.nodes.len() {
-            debug!("build_layer_order_recurse({recursion}, {layer_list:?}, {node_index}) for");
+            debug!("build_layer_order_recurse({recursion}, {layer_list:?}, {node_index}, {split:?}) for");
             debug!("{self:#?}");
             self.print_net_structure();
             panic!()
@@ -289,22 +482,54 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         for connection_index in self.get_node(node_index).input_connections.iter() {
             let connection = &self.connections[connection_index.1];
             assert_eq!(node_index, connection.output_node);
-            layer = layer.max(1 + self.build_layer_order_recurse(recursion + 1, layer_list, connection.input_node));
+            // A split connection goes through the new node, a layer further along
+            let step = if split == Some(connection.index) { 2 } else { 1 };
+            layer = layer.max(step + self.build_layer_order_recurse(recursion + 1, layer_list, connection.input_node, split));
         }
         layer_list.insert(node_index, layer);
         layer
     }
 
 
+    /// Output nodes with no chain of enabled connections back to any input node.  Such an output
+    /// always emits the same value, whatever the inputs, so the net has effectively lost an action.
+    pub fn unreachable_outputs(&self) -> Vec<NodeIndex> {
+        let mut is_reachable = vec![None; self.nodes.len()];
+        self.nodes.iter()
+            .filter(|n| n.layer == Layer::Output)
+            .filter(|n| !self.is_reachable_from_input_recurse(0, &mut is_reachable, n.index))
+            .map(|n| n.index)
+            .collect()
+    }
+
+    // Like build_evaluation_order_recurse(), walks enabled connections backward from `node_index`,
+    // remembering the answer for each node visited.
+    fn is_reachable_from_input_recurse(&self, recursion: usize, is_reachable: &mut [Option<bool>], node_index: NodeIndex) -> bool {
+        assert!(recursion <= 2 * self.nodes.len());
+        assert_eq!(self.id, node_index.0);
+        if let Some(answer) = is_reachable[node_index.1] { return answer; }
+        let node = self.get_node(node_index);
+        let answer = node.layer == Layer::Input || node.input_connections.iter()
+            .map(|&connection_index| self.get_connection(connection_index))
+            .filter(|connection| connection.is_enabled)
+            .any(|connection| self.is_reachable_from_input_recurse(recursion + 1, is_reachable, connection.input_node));
+        is_reachable[node_index.1] = Some(answer);
+        answer
+    }
+
+
     pub fn evaluate(&mut self) {
         assert!(self.is_evaluation_order_up_to_date);
         //assert!(self.node_values.len() > self.nodes.len());
 
         // We have already computed a correct order in which to evaluate nodes, and the caller
         // has filled in the self.node_values for all input nodes, so we now visit nodes in 
-        // order and evaluate them.
+        // order and evaluate them.  Disabled connections contribute nothing, and input nodes keep
+        // the values they were given rather than being "evaluated" from their (nonexistent) inputs.
         for &node_index in self.node_order_list.iter() {
+            if self.get_node(node_index).layer == Layer::Input { continue; }
             let inputs_sum = self.get_node(node_index).input_connections.iter()
+                .filter(|&&connection_index| self.get_connection(connection_index).is_enabled)
                 .map(|connection_index| {
                     let connection = &self.connections[connection_index.1];
                     self.get_node(connection.input_node).value * connection.weight
@@ -314,18 +539,55 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
                 let node = &mut self.nodes[node_index.1];
                 node.value = node.apply_activation_function(inputs_sum);
             }
+            if cfg!(debug_assertions) && !self.nodes[node_index.1].value.is_finite() {
+                self.warn_non_finite(node_index, inputs_sum);
+            }
+        }
+    }
+
+    /// Logs a node whose value became NaN or infinite, along with each input that fed it.
+    fn warn_non_finite(&self, node_index: NodeIndex, inputs_sum: f32) {
+        let node = self.get_node(node_index);
+        warn!("{}: node {node_index} ({:?}) = {} from inputs sum {inputs_sum}", self.id, node.activation_function, node.value);
+        for &connection_index in node.input_connections.iter().filter(|&&ci| self.get_connection(ci).is_enabled) {
+            let connection = self.get_connection(connection_index);
+            warn!("    connection {connection_index}: {} * weight {}", self.get_node(connection.input_node).value, connection.weight);
         }
     }
 
+    /// A copy of this net under a new NetId (which every NodeIndex and ConnectionIndex carries),
+    /// keeping its node and connection ids, e.g. to mutate a copy while keeping the original.
+    pub fn clone_as_new_net(&self) -> Self {
+        let mut net = Self {
+            id: NetId::new_unique(),
+            net_params: self.net_params.clone(),
+            nodes: Vec::with_capacity(self.nodes.len()),
+            map_node_id_to_index: HashMap::with_capacity(self.nodes.len()),
+            connections: Vec::with_capacity(self.connections.len()),
+            map_connection_id_to_index: HashMap::with_capacity(self.connections.len()),
+            fitness_info: Fit::default(),
+            is_evaluation_order_up_to_date: false,
+            node_order_list: Vec::new(),
+        };
+        for node in self.nodes.iter() {
+            net.add_node(Some(node.id), node.activation_function, Some(node.layer), node.value);
+        }
+        for c in self.connections.iter() {
+            net.add_connection(Some(c.id), c.weight, c.is_enabled, NodeIndex(net.id, c.input_node.1), NodeIndex(net.id, c.output_node.1));
+        }
+        net.build_evaluation_order();
+        net
+    }
+
     fn adjust_prob(p: f64, adjuster: f64) -> f64 {
         f64::min(1.0, p * adjuster)
     }
     
-    pub(super) fn cross_into_new_net(&self, other: &Self, mut_params: &MutationParams, mutation_multiplier: f64) -> Self {
+    pub(super) fn cross_into_new_net(&self, other: &Self, mut_params: &MutationParams, mutation_multiplier: f64, rng: &mut impl Rng) -> Self {
         // Choose a "winning" parent, partially based on fitnesses
         let (winner, loser) = if self.fitness_info.get_fitness() >= other.fitness_info.get_fitness() { (self, other) } else { (other, self) };
         // Small chance to actually choose the "loser" as the winner:
-        let (winner, loser) = if thread_rng().gen_bool(0.2) { (loser, winner) } else { (winner, loser) };
+        let (winner, loser) = if rng.gen_bool(0.2) { (loser, winner) } else { (winner, loser) };
 
         // Initialize the child Net
         let max_node_count = self.nodes.len().max(other.nodes.len());
@@ -345,17 +607,17 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
 
         // Remove a node by selecting one NOT to copy!
         let mut node_id_dont_copy: Option<NodeId> = None;
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_remove_node, mutation_multiplier)) {
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_remove_node, mutation_multiplier)) {
             let hidden = winner.nodes.iter().filter_map(|n| if let Layer::Hidden(_) = n.layer { Some(n.id) } else { None }).collect::<Vec<_>>();
-            if let Some(&x) = hidden.choose(&mut thread_rng()) {
+            if let Some(&x) = hidden.choose(rng) {
                 node_id_dont_copy = Some(x);
             }
         }
 
         // Remove a connection by selecting one NOT to copy!
         let mut connection_id_dont_copy: Option<ConnectionId> = None;
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_remove_connection, mutation_multiplier)) {
-            if let Some(x) = winner.connections.choose(&mut thread_rng()) {
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_remove_connection, mutation_multiplier)) {
+            if let Some(x) = winner.connections.choose(rng) {
                 connection_id_dont_copy = Some(x.id);
             }
         }
@@ -369,7 +631,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
             }
             let node_to_clone = match loser.map_node_id_to_index.get(&node_winner.id) {
                 None => node_winner,
-                Some(&node_index_loser) => if thread_rng().gen_bool(0.5) { node_winner } else { loser.get_node(node_index_loser) },
+                Some(&node_index_loser) => if rng.gen_bool(0.5) { node_winner } else { loser.get_node(node_index_loser) },
             };
             net_child.add_node(Some(node_to_clone.id), node_to_clone.activation_function, Some(node_to_clone.layer), node_to_clone.value);
         }
@@ -388,7 +650,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
             }
             let (net_of_clone, connection_to_clone) = match loser.map_connection_id_to_index.get(&connection_winner.id) {
                 None => (winner, connection_winner),
-                Some(connection_index_loser) => if thread_rng().gen_bool(0.5) { 
+                Some(connection_index_loser) => if rng.gen_bool(0.5) { 
                     (winner, connection_winner)
                 } else { 
                     (loser, &loser.connections[connection_index_loser.1])
@@ -412,7 +674,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         net_child.verify_invariants();
 
         trace!("NET: {net_child:#?}");
-        net_child.mutate_self(mut_params, mutation_multiplier);
+        net_child.mutate_self(mut_params, mutation_multiplier, rng);
         net_child
     }
 
@@ -477,14 +739,14 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         // TODO: Not sure of an easy way to do this!
     }
 
-    fn choose_index<T:Copy>(id_list: &[T]) -> T {
-        let i = thread_rng().gen_range(0..id_list.len());
+    fn choose_index<T:Copy>(id_list: &[T], rng: &mut impl Rng) -> T {
+        let i = rng.gen_range(0..id_list.len());
         id_list[i]
     }
 
-    fn choose_index_not<T:Copy+PartialEq>(id_list: &[T], not: T) -> T {
+    fn choose_index_not<T:Copy+PartialEq>(id_list: &[T], not: T, rng: &mut impl Rng) -> T {
         for _ in 0..20 {
-            let i = thread_rng().gen_range(0..id_list.len());
+            let i = rng.gen_range(0..id_list.len());
             let id = id_list[i];
             if id != not { return id; }
         }
@@ -492,26 +754,30 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
     }
 
 
-    pub(super) fn mutate_self(&mut self, mut_params: &MutationParams, mutation_multiplier: f64) {
+    pub(super) fn mutate_self(&mut self, mut_params: &MutationParams, mutation_multiplier: f64, rng: &mut impl Rng) {
         let node_index_list   = self.nodes.iter().map(|n| n.index).collect::<Vec<_>>();
-        let input_and_hidden  = self.nodes.iter().filter_map(|n| if n.layer != Layer::Output && n.layer != Layer::Unreachable { Some(n.index) } else { None }).collect::<Vec<_>>();
-        let hidden_and_output = self.nodes.iter().filter_map(|n| if n.layer != Layer::Input  && n.layer != Layer::Unreachable { Some(n.index) } else { None }).collect::<Vec<_>>();
 
         // Change a single node's activation function
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_mutate_activation_function_of_node, mutation_multiplier)) && !node_index_list.is_empty() {
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_mutate_activation_function_of_node, mutation_multiplier)) && !node_index_list.is_empty() {
             trace!("Mutating node activation function");
-            let node_mutate = self.get_node_mut(Self::choose_index(&node_index_list));
-            if node_mutate.layer != Layer::Input { node_mutate.activation_function = ActivationFunction::choose_random(); }
+            let node_mutate = self.get_node_mut(Self::choose_index(&node_index_list, rng));
+            if node_mutate.layer != Layer::Input {
+                node_mutate.activation_function = if mut_params.activation_function_weights.is_empty() {
+                    ActivationFunction::choose_random(rng)
+                } else {
+                    ActivationFunction::choose_random_weighted(&mut_params.activation_function_weights, rng)
+                };
+            }
         }
 
         // Change all connections' weight
         for connection_mutate in self.connections.iter_mut() {
-            if thread_rng().gen_bool(mut_params.prob_reset_weight_when_mutating) {
-                connection_mutate.weight = thread_rng().gen::<f32>() * 2.0 - 1.0;
+            if rng.gen_bool(mut_params.prob_reset_weight_when_mutating) {
+                connection_mutate.weight = rng.gen::<f32>() * 2.0 - 1.0;
             } else {
                 // 0>=max_weight_change_frac>1.0 i.e. w *= (1.0 - rand_between(0.0, max_weight_change_frac)).pow(+/-1.0)
-                let pow = if thread_rng().gen_bool(0.5) { 1.0 } else { -1.0 };
-                connection_mutate.weight *= (1.0 - (thread_rng().gen::<f32>() * mut_params.max_weight_change_frac)).powf(pow);
+                let pow = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                connection_mutate.weight *= (1.0 - (rng.gen::<f32>() * mut_params.max_weight_change_frac)).powf(pow);
             }
         }
         //let connection_index_list = self.connections.iter().map(|c| c.index).collect::<Vec<_>>();
@@ -529,16 +795,40 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
 
         // Toggle a conneciton's is_enabled
         let connection_index_list = self.connections.iter().map(|c| c.index).collect::<Vec<_>>();
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_toggle_enabled, mutation_multiplier)) && !connection_index_list.is_empty() {
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_toggle_enabled, mutation_multiplier)) && !connection_index_list.is_empty() {
             trace!("Mutating connection is_enabled");
-            let connection_mutate = self.get_connection_mut(Self::choose_index(&connection_index_list));
+            let connection_mutate = self.get_connection_mut(Self::choose_index(&connection_index_list, rng));
             connection_mutate.is_enabled = !connection_mutate.is_enabled;
         }
 
+        // Structural mutations, in up to structural_mutations_per_call rounds.  Between rounds, the
+        // layers are brought up to date, so each round can build on (and not cycle with) the last.
+        for round in 0..mut_params.structural_mutations_per_call.max(1) {
+            if round > 0 {
+                self.is_evaluation_order_up_to_date = false;
+                self.build_evaluation_order();
+            }
+            self.mutate_structure_once(mut_params, mutation_multiplier, rng);
+        }
+
+        self.is_evaluation_order_up_to_date = false;
+        trace!(target: "net_EXTREME", "NET: {self:#?}");
+        self.build_evaluation_order();
+        trace!(target: "net_EXTREME", "NET: {self:#?}");
+        self.verify_invariants();
+    }
+
+    /// One round of `mutate_self()`'s structural mutations: maybe add a connection, then maybe
+    /// split one with a new node.  Assumes the nodes' layers are up to date.
+    fn mutate_structure_once(&mut self, mut_params: &MutationParams, mutation_multiplier: f64, rng: &mut impl Rng) {
+        let input_and_hidden  = self.nodes.iter().filter_map(|n| if n.layer != Layer::Output && n.layer != Layer::Unreachable { Some(n.index) } else { None }).collect::<Vec<_>>();
+        let hidden_and_output = self.nodes.iter().filter_map(|n| if n.layer != Layer::Input  && n.layer != Layer::Unreachable { Some(n.index) } else { None }).collect::<Vec<_>>();
+        let connection_index_list = self.connections.iter().map(|c| c.index).collect::<Vec<_>>();
+
         // Add a connection
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_add_connection, mutation_multiplier)) && input_and_hidden.len() > 1 {
-            let mut index_from = Self::choose_index(&input_and_hidden);
-            let mut index_to   = Self::choose_index_not(&hidden_and_output, index_from);
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_add_connection, mutation_multiplier)) && input_and_hidden.len() > 1 {
+            let mut index_from = Self::choose_index(&input_and_hidden, rng);
+            let mut index_to   = Self::choose_index_not(&hidden_and_output, index_from, rng);
             let from = self.get_node(index_from);
             let to   = self.get_node(index_to  );
             // If we chose a "from" that comes before a "to", simply swap them
@@ -566,7 +856,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
             });
             let connection_index_new = self.add_connection(
                 None, 
-                thread_rng().gen::<f32>() * 2.0 - 1.0, 
+                rng.gen::<f32>() * 2.0 - 1.0, 
                 true, 
                 index_from, 
                 index_to
@@ -579,31 +869,61 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         // made a new connection between two nodes in the same hidden layer
 
         // Add node
-        if thread_rng().gen_bool(Self::adjust_prob(mut_params.prob_add_node, mutation_multiplier)) && !connection_index_list.is_empty() {
+        if rng.gen_bool(Self::adjust_prob(mut_params.prob_add_node, mutation_multiplier)) && !connection_index_list.is_empty() {
             // Choose a random Connection, and split it into two, inserting the new node inbetween 
             // and setting old.is_enabled = false
-            let connection_index_old = Self::choose_index(&connection_index_list);
-            let connection_old = self.get_connection_mut(connection_index_old);
-            connection_old.is_enabled = false;
-            let weight_connection_new_a = connection_old.weight;
-            let node_index_input  = connection_old. input_node;
-            let node_index_output = connection_old.output_node;
-            let node_output = self.get_node(node_index_output);
-            let activation_function = node_output.activation_function;
-
-            let node_index_new = self.add_node(None, activation_function, None, 0.0);
-            let connection_index_new_a = self.add_connection(None, weight_connection_new_a, true, /*from*/ node_index_input, /*to*/ node_index_new);
-            let connection_index_new_b = self.add_connection(None, activation_function.get_neutral_value(), true, /*from*/ node_index_new, /*to*/ node_index_output);
-            trace!("Mutating by adding node {} and connections {} and {}", node_index_new, connection_index_new_a, connection_index_new_b);
+            let connection_index_old = Self::choose_index(&connection_index_list, rng);
+            match mut_params.max_hidden_depth {
+                None => self.split_connection(connection_index_old),
+                Some(max_hidden_depth) => {
+                    // Splitting can push every node downstream of the split deeper
+                    if self.max_hidden_layer_after_split(connection_index_old) <= max_hidden_depth {
+                        self.split_connection(connection_index_old);
+                    } else {
+                        trace!("Not splitting {connection_index_old}, which would exceed max_hidden_depth={max_hidden_depth}");
+                    }
+                }
+            }
         }
-
-        self.is_evaluation_order_up_to_date = false;
-        trace!(target: "net_EXTREME", "NET: {self:#?}");
-        self.build_evaluation_order();
-        trace!(target: "net_EXTREME", "NET: {self:#?}");
-        self.verify_invariants();
     }
     
+    /// Disables the connection and inserts a new node in its place.
+    fn split_connection(&mut self, connection_index_old: ConnectionIndex) {
+        let connection_old = self.get_connection_mut(connection_index_old);
+        connection_old.is_enabled = false;
+        let weight_connection_new_a = connection_old.weight;
+        let node_index_input  = connection_old. input_node;
+        let node_index_output = connection_old.output_node;
+        let node_output = self.get_node(node_index_output);
+        let activation_function = node_output.activation_function;
+
+        let node_index_new = self.add_node(None, activation_function, None, 0.0);
+        let connection_index_new_a = self.add_connection(None, weight_connection_new_a, true, /*from*/ node_index_input, /*to*/ node_index_new);
+        let connection_index_new_b = self.add_connection(None, activation_function.get_neutral_value(), true, /*from*/ node_index_new, /*to*/ node_index_output);
+        trace!("Mutating by adding node {} and connections {} and {}", node_index_new, connection_index_new_a, connection_index_new_b);
+        self.is_evaluation_order_up_to_date = false;
+    }
+
+    /// The deepest `Layer::Hidden(n)` that `build_evaluation_order()` would assign after
+    /// `split_connection(connection_index)`, or 0 if none, without doing the split.
+    fn max_hidden_layer_after_split(&self, connection_index: ConnectionIndex) -> u16 {
+        let mut layer_list = HashMap::<NodeIndex, u16>::with_capacity(self.nodes.len());
+        for node in self.nodes.iter().filter(|n| n.layer == Layer::Output) {
+            self.build_layer_order_recurse(0, &mut layer_list, node.index, Some(connection_index));
+        }
+        let max_hidden_layer = self.nodes.iter()
+            .filter(|n| n.layer != Layer::Input && n.layer != Layer::Output)
+            .filter_map(|n| layer_list.get(&n.index).copied())
+            .max()
+            .unwrap_or(0);
+        // The new node is only laid out if the split connection's output node is
+        let connection = self.get_connection(connection_index);
+        match (layer_list.get(&connection.input_node), layer_list.contains_key(&connection.output_node)) {
+            (Some(&layer_input), true) => max_hidden_layer.max(layer_input + 1),
+            _ => max_hidden_layer,
+        }
+    }
+
     pub(crate) fn set_inputs(&mut self, inputs: &[f32]) {
         assert_eq!(inputs.len(), self.net_params.input_count);
         for (i, node) in self.nodes.iter_mut().enumerate().take(self.net_params.input_count) {
@@ -621,7 +941,192 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
         v
     }
 
+    /// The outputs, in order, each labeled with its name from `net_params.output_names`, or with
+    /// its index when the net has no output names.
+    pub fn describe_outputs(&self) -> Vec<(String, f32)> {
+        let outputs = self.get_outputs();
+        match self.net_params.output_names {
+            Some(names) => names.iter().map(|name| name.to_string()).zip(outputs).collect(),
+            None => outputs.into_iter().enumerate().map(|(i, value)| (i.to_string(), value)).collect(),
+        }
+    }
+
+    /// Repair pass for imported nets (e.g. old stash files): folds every enabled connection that
+    /// duplicates an earlier enabled connection between the same two nodes into that earlier one,
+    /// summing their weights, so evaluation is unchanged.  Returns how many connections were removed.
+    pub fn merge_duplicate_connections(&mut self) -> usize {
+        let mut first_of_pair = HashMap::<(NodeIndex, NodeIndex), usize>::with_capacity(self.connections.len());
+        let mut is_duplicate = vec![false; self.connections.len()];
+        for (i, is_duplicate_i) in is_duplicate.iter_mut().enumerate() {
+            let connection = &self.connections[i];
+            if !connection.is_enabled { continue; }
+            match first_of_pair.get(&(connection.input_node, connection.output_node)) {
+                None => { first_of_pair.insert((connection.input_node, connection.output_node), i); }
+                Some(&first) => {
+                    let weight = connection.weight;
+                    self.connections[first].weight += weight;
+                    *is_duplicate_i = true;
+                }
+            }
+        }
+        self.remove_connections(&is_duplicate)
+    }
+
+    /// Permanently removes disabled connections, returning how many were removed.  Opt-in, since
+    /// crossover and mutation may otherwise re-enable them.  With `keep_split_connections`, a
+    /// disabled connection is kept while a node split from it (i.e. a hidden node with enabled
+    /// connections from its input and to its output) still bridges the same two nodes.
+    pub fn prune_disabled_connections(&mut self, keep_split_connections: bool) -> usize {
+        let is_bridged = |connection: &Connection| {
+            self.get_node(connection.output_node).input_connections.iter()
+                .map(|&ci| self.get_connection(ci))
+                .filter(|c| c.is_enabled && self.get_node(c.input_node).layer != Layer::Input)
+                .any(|c| self.get_node(c.input_node).input_connections.iter()
+                    .map(|&ci| self.get_connection(ci))
+                    .any(|c| c.is_enabled && c.input_node == connection.input_node))
+        };
+        let is_removed = self.connections.iter()
+            .map(|c| !c.is_enabled && (!keep_split_connections || !is_bridged(c)))
+            .collect::<Vec<_>>();
+        self.remove_connections(&is_removed)
+    }
+
+    /// For every connection that `other` also has (i.e. with the same ConnectionId), takes its
+    /// weight from `other`.  The structure, and connections only this net has, are untouched.
+    pub fn copy_weights_from(&mut self, other: &Net<Fit>) {
+        for connection in self.connections.iter_mut() {
+            if let Some(&ci) = other.map_connection_id_to_index.get(&connection.id) {
+                connection.weight = other.get_connection(ci).weight;
+            }
+        }
+    }
+
+    /// Adds Gaussian noise, with standard deviation `sigma`, to every connection weight.
+    pub fn jitter_weights(&mut self, sigma: f32, rng: &mut impl Rng) {
+        for connection in self.connections.iter_mut() {
+            // Box-Muller transform; u1 is in (0, 1], so its ln() is finite
+            let (u1, u2) = (1.0 - rng.gen::<f32>(), rng.gen::<f32>());
+            let normal = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+            connection.weight += sigma * normal;
+        }
+    }
+
+    /// Rounds every connection weight to `decimal_places` decimal places, so that the net
+    /// serializes to much shorter JSON.  Loading needs no matching step: the rounded weights
+    /// parse back exactly, though the net's outputs shift slightly from the unrounded ones.
+    pub fn quantize_weights(&mut self, decimal_places: u32) {
+        let scale = 10_f32.powi(decimal_places as i32);
+        for connection in self.connections.iter_mut() {
+            connection.weight = (connection.weight * scale).round() / scale;
+        }
+    }
+
+    fn remove_connections(&mut self, is_removed: &[bool]) -> usize {
+        let removed_count = is_removed.iter().filter(|&&r| r).count();
+        if removed_count == 0 { return 0; }
+
+        // Re-add the survivors so that indexes, maps, and input_connections are all rebuilt
+        let connections_old = std::mem::take(&mut self.connections);
+        self.map_connection_id_to_index.clear();
+        for node in self.nodes.iter_mut() { node.input_connections.clear(); }
+        for (connection, &is_removed) in connections_old.into_iter().zip(is_removed) {
+            if is_removed { continue; }
+            self.add_connection(Some(connection.id), connection.weight, connection.is_enabled, connection.input_node, connection.output_node);
+        }
+        self.is_evaluation_order_up_to_date = false;
+        self.build_evaluation_order();
+        self.verify_invariants();
+        removed_count
+    }
+
+    const COMPATIBILITY_DISJOINT_COEFFICIENT: f32 = 1.0;
+    const COMPATIBILITY_WEIGHT_COEFFICIENT:   f32 = 0.4;
+
+    /// NEAT-style genetic distance: connections are matched by ConnectionId, so the distance grows
+    /// with the fraction of connections only one of the nets has (disjoint and excess genes alike),
+    /// plus the average weight difference of the connections they share.  Identical nets are 0.0.
+    pub fn compatibility_distance(&self, other: &Self) -> f32 {
+        let mut matching = 0;
+        let mut weight_difference_sum = 0.0;
+        for connection in self.connections.iter() {
+            if let Some(&index_other) = other.map_connection_id_to_index.get(&connection.id) {
+                matching += 1;
+                weight_difference_sum += (connection.weight - other.connections[index_other.1].weight).abs();
+            }
+        }
+        let disjoint = self.connections.len() + other.connections.len() - 2 * matching;
+        let n = self.connections.len().max(other.connections.len()).max(1) as f32;
+        let weight_difference_avg = if matching == 0 { 0.0 } else { weight_difference_sum / matching as f32 };
+        Self::COMPATIBILITY_DISJOINT_COEFFICIENT * disjoint as f32 / n + Self::COMPATIBILITY_WEIGHT_COEFFICIENT * weight_difference_avg
+    }
+
+    /// A hash of the whole genome: every node's id and activation function, and every
+    /// connection's id, endpoints, weight, and enabled flag.  Not the NetId or fitness, so a
+    /// `clone_as_new_net()` hashes the same as its original.
+    pub fn structure_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for node in self.nodes.iter() {
+            (node.id, node.activation_function).hash(&mut hasher);
+        }
+        for c in self.connections.iter() {
+            (c.id, self.get_node(c.input_node).id, self.get_node(c.output_node).id, c.weight.to_bits(), c.is_enabled).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// How many nodes are in each hidden layer, keyed by `Layer::Hidden` number.  Inputs, outputs,
+    /// and Unreachable nodes aren't counted.  Layers are assigned by `build_evaluation_order()`.
+    pub fn layer_sizes(&self) -> BTreeMap<u16, usize> {
+        assert!(self.is_evaluation_order_up_to_date);
+        let mut sizes = BTreeMap::new();
+        for node in self.nodes.iter() {
+            if let Layer::Hidden(h) = node.layer { *sizes.entry(h).or_insert(0) += 1; }
+        }
+        sizes
+    }
+
+    /// Exports the net as a dense weight matrix for numerical tooling (e.g. NumPy).  Nodes are
+    /// ordered inputs first, then hidden nodes by layer (with Unreachable nodes last among them),
+    /// then outputs; the returned NodeIds give that order.  Entry `[i][j]` is the sum of the
+    /// weights of the enabled connections from node i to node j, or 0.0 if there are none.
+    pub fn to_adjacency_matrix(&self) -> (Vec<NodeId>, Vec<Vec<f32>>) {
+        let sort_key = |layer: Layer| match layer {
+            Layer::Input       => 0,
+            Layer::Hidden(h)   => h as usize + 1,
+            Layer::Unreachable => u16::MAX as usize + 1,
+            Layer::Output      => u16::MAX as usize + 2,
+        };
+        let mut order = self.nodes.iter().map(|n| n.index).collect::<Vec<_>>();
+        order.sort_by_key(|&i| (sort_key(self.get_node(i).layer), i.1));   // Stable by index within a layer
+        let mut position = vec![0; self.nodes.len()];
+        for (pos, i) in order.iter().enumerate() { position[i.1] = pos; }
+
+        let mut matrix = vec![vec![0.0_f32; order.len()]; order.len()];
+        for c in self.connections.iter().filter(|c| c.is_enabled) {
+            matrix[position[c.input_node.1]][position[c.output_node.1]] += c.weight;
+        }
+        (order.iter().map(|&i| self.get_node(i).id).collect(), matrix)
+    }
+
     pub fn print_net_structure(&self) { // FUTURE: rewrite for being logging compatible
+        print!("{}", self.structure_string());
+    }
+
+    /// A fixed-width, grep-friendly one-liner: `#<id> n<nodes> c<enabled>/<total> h<hidden> f<fitness>`.
+    pub fn to_compact_string(&self) -> String {
+        format!("{:<7} {:<5} {:<11} {:<4} f{:.1}",
+            format!("#{}", self.id.0),
+            format!("n{}", self.node_count()),
+            format!("c{}/{}", self.enabled_connection_count(), self.connection_count()),
+            format!("h{}", self.hidden_node_count()),
+            self.fitness_info.get_fitness())
+    }
+
+    /// One line per node, listing its incoming connections, with a blank line after the inputs and outputs.
+    pub fn structure_string(&self) -> String {
+        use std::fmt::Write;
+        let mut s = String::new();
         let mut prev = Layer::Input;
         for n in self.nodes.iter() {
             let index = n.index.1;
@@ -631,10 +1136,10 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
                 Layer::Unreachable => "U".to_string(),
                 Layer::Hidden(h) => format!("H{h}"),
             };
-            print!("N{index}/{kind} : ");
+            write!(s, "N{index}/{kind} : ").unwrap();
             match n.layer {
-                Layer::Input  => if let Some(x) = self.net_params. input_names { print!("{} : ", x[index]); },
-                Layer::Output => if let Some(x) = self.net_params.output_names { print!("{} : ", x[index - self.net_params.input_count]); },
+                Layer::Input  => if let Some(x) = self.net_params. input_names { write!(s, "{} : ", x[index]).unwrap(); },
+                Layer::Output => if let Some(x) = self.net_params.output_names { write!(s, "{} : ", x[index - self.net_params.input_count]).unwrap(); },
                 _ => {},
             }
             for (i, c) in n.input_connections.iter().map(|&i| self.get_connection(i)).enumerate() {
@@ -643,13 +1148,14 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
                 let tf = if c.is_enabled { "t" } else { "FALSE" };
                 let from = c.input_node.1;
                 let to = c.output_node.1;
-                print!("{comma}C{index}({tf}:N{from}->N{to})");
+                write!(s, "{comma}C{index}({tf}:N{from}->N{to})").unwrap();
             }
-            println!();
-            if prev == Layer::Input  && n.layer != Layer::Input  { println!(); }
-            if prev == Layer::Output && n.layer != Layer::Output { println!(); }
+            writeln!(s).unwrap();
+            if prev == Layer::Input  && n.layer != Layer::Input  { writeln!(s).unwrap(); }
+            if prev == Layer::Output && n.layer != Layer::Output { writeln!(s).unwrap(); }
             prev = n.layer;
         }
+        s
     }
 }
 
@@ -659,6 +1165,7 @@ impl <Fit> Net<Fit> where Fit: FitnessInfo {
 #[cfg(test)]
 mod tests {
     use log::info;
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
 
     use super::*;
 
@@ -668,6 +1175,20 @@ mod tests {
         net.verify_invariants();
     }
 
+    #[test]
+    fn test_mutation_presets_are_probabilities() {
+        for params in [MutationParams::neat_default(), MutationParams::aggressive(), MutationParams::weights_only()] {
+            let probs = [
+                params.prob_mutate_activation_function_of_node, params.prob_mutate_weight, params.prob_reset_weight_when_mutating,
+                params.prob_toggle_enabled, params.prob_remove_connection, params.prob_add_connection,
+                params.prob_remove_node, params.prob_add_node,
+            ];
+            assert!(probs.iter().all(|p| (0.0..=1.0).contains(p)), "{params:?}");
+            assert!((0.0..=1.0).contains(&params.max_weight_change_frac), "{params:?}");
+        }
+        assert_eq!(0.0, MutationParams::weights_only().prob_add_node);
+    }
+
     #[test]
     fn test_mutations_separately() {
         let net = Net::<f32>::new(NetParams::from_size(10, 4));
@@ -676,12 +1197,15 @@ mod tests {
             prob_add_connection: 0.0,
             prob_add_node: 0.0,
             prob_mutate_activation_function_of_node: 0.0,
+            activation_function_weights: Vec::new(),
             prob_mutate_weight: 0.0,
             prob_reset_weight_when_mutating: 0.0,
             max_weight_change_frac: 0.0,
             prob_toggle_enabled: 0.0,
             prob_remove_connection: 0.0,
             prob_remove_node: 0.0,
+            max_hidden_depth: None,
+            structural_mutations_per_call: 1,
         };
         let mut param_add_connection = params.clone();  param_add_connection.prob_add_connection = 1.0;
         let mut param_add_node       = params.clone();  param_add_node      .prob_add_node       = 1.0;
@@ -691,22 +1215,70 @@ mod tests {
         let mut param_mutate_af      = params.clone();  param_mutate_af     .prob_mutate_activation_function_of_node = 1.0;
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_add_connection, 1.0);
+        net.mutate_self(&param_add_connection, 1.0, &mut thread_rng());
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_add_node, 1.0);
+        net.mutate_self(&param_add_node, 1.0, &mut thread_rng());
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_toggle_enabled, 1.0);
+        net.mutate_self(&param_toggle_enabled, 1.0, &mut thread_rng());
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_mutate_weight, 1.0);
+        net.mutate_self(&param_mutate_weight, 1.0, &mut thread_rng());
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_mutate_weight2, 1.0);
+        net.mutate_self(&param_mutate_weight2, 1.0, &mut thread_rng());
 
         let mut net = Net::<f32>::new(NetParams::from_size(10, 4));
-        net.mutate_self(&param_mutate_af, 1.0);
+        net.mutate_self(&param_mutate_af, 1.0, &mut thread_rng());
+    }
+
+    #[test]
+    fn test_max_hidden_depth() {
+        let params = MutationParams {
+            prob_add_connection: 0.0,
+            prob_add_node: 1.0,
+            prob_mutate_activation_function_of_node: 0.0,
+            activation_function_weights: Vec::new(),
+            prob_mutate_weight: 0.0,
+            prob_reset_weight_when_mutating: 0.0,
+            max_weight_change_frac: 0.0,
+            prob_toggle_enabled: 0.0,
+            prob_remove_connection: 0.0,
+            prob_remove_node: 0.0,
+            max_hidden_depth: Some(3),
+            structural_mutations_per_call: 1,
+        };
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 2));
+        net.add_connection(None, 1.0, true, NodeIndex(net.id, 0), NodeIndex(net.id, 2));
+        net.add_connection(None, 1.0, true, NodeIndex(net.id, 1), NodeIndex(net.id, 3));
+        net.build_evaluation_order();
+        for _ in 0..200 {
+            net.mutate_self(&params, 1.0, &mut thread_rng());
+            assert!(net.nodes.iter().all(|n| !matches!(n.layer, Layer::Hidden(h) if h > 3)), "{}", net.structure_string());
+        }
+        // Splitting does happen, right up to the cap
+        assert_eq!(Some(&3), net.layer_sizes().keys().max());
+    }
+
+    #[test]
+    fn test_max_hidden_layer_after_split_predicts_splitting() {
+        let params = MutationParams { prob_add_node: 0.5, prob_add_connection: 0.5, ..MutationParams::neat_default() };
+        let mut net = Net::<f32>::new(NetParams::from_size(3, 2));
+        net.add_connection(None, 1.0, true, NodeIndex(net.id, 0), NodeIndex(net.id, 3));
+        net.add_connection(None, 1.0, true, NodeIndex(net.id, 2), NodeIndex(net.id, 4));
+        let mut rng = StdRng::seed_from_u64(1662);
+        for _ in 0..20 {
+            net.build_evaluation_order();
+            net.mutate_self(&params, 1.0, &mut rng);
+        }
+        for connection_index in net.connections.iter().map(|c| c.index) {
+            let mut net_split = net.clone();
+            net_split.split_connection(connection_index);
+            net_split.build_evaluation_order();
+            let max_hidden_layer = net_split.layer_sizes().keys().max().copied().unwrap_or(0);
+            assert_eq!(max_hidden_layer, net.max_hidden_layer_after_split(connection_index), "{}", net.structure_string());
+        }
     }
 
     #[test]
@@ -717,20 +1289,97 @@ mod tests {
             prob_add_connection: 0.1,
             prob_add_node: 0.1,
             prob_mutate_activation_function_of_node: 0.1,
+            activation_function_weights: Vec::new(),
             prob_mutate_weight: 0.1,
             prob_reset_weight_when_mutating: 0.1,
             max_weight_change_frac: 0.1,
             prob_toggle_enabled: 0.1,
             prob_remove_connection: 0.0,
             prob_remove_node: 0.0,
+            max_hidden_depth: None,
+            structural_mutations_per_call: 1,
         };
         for _ in 0..100 {
-            net.mutate_self(&params, 1.0);
+            net.mutate_self(&params, 1.0, &mut thread_rng());
         }
         info!("Mutated Net = {net:#?}");
     }
 
 
+    #[test]
+    fn test_burst_mutation_adds_several_nodes() {
+        let mut rng = StdRng::seed_from_u64(1707);
+        let mut net = Net::<f32>::new(NetParams::from_size(4, 4));
+        let grow = MutationParams { prob_add_connection: 1.0, ..MutationParams::weights_only() };
+        for _ in 0..3 { net.mutate_self(&grow, 1.0, &mut rng); }
+        assert!(net.connection_count() > 0);
+
+        let burst = MutationParams { prob_add_node: 1.0, structural_mutations_per_call: 3, ..MutationParams::weights_only() };
+        for _ in 0..10 {
+            let nodes_before = net.node_count();
+            net.mutate_self(&burst, 1.0, &mut rng);
+            assert_eq!(nodes_before + 3, net.node_count());
+            assert!(net.is_evaluation_order_up_to_date);
+        }
+        let single = MutationParams { prob_add_node: 1.0, ..MutationParams::weights_only() };
+        let nodes_before = net.node_count();
+        net.mutate_self(&single, 1.0, &mut rng);
+        assert_eq!(nodes_before + 1, net.node_count());
+    }
+
+    #[test]
+    fn test_cross_inherits_matching_genes_from_either_and_disjoint_from_winner() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::Sigmoid, layer: Layer::Output };
+        let hidden = NodeSpec { activation_function: ActivationFunction::ReLU,    layer: Layer::Hidden(1) };
+        let mut net_a = Net::<f32>::from_spec(&NetSpec {
+            net_params: NetParams::from_size(2, 1),
+            nodes: vec![input, input, output, hidden],
+            connections: vec![
+                ConnectionSpec { from: 0, to: 3, weight: 0.25, is_enabled: true },
+                ConnectionSpec { from: 3, to: 2, weight: 0.25, is_enabled: true },
+                ConnectionSpec { from: 1, to: 2, weight: 0.25, is_enabled: true },
+            ],
+        }).unwrap();
+        // B shares A's genes (with other weights), plus a node splitting 1->2; A adds 0->2
+        let mut net_b = net_a.clone_as_new_net();
+        for c in net_b.connections.iter_mut() { c.weight = -0.5; }
+        net_b.split_connection(ConnectionIndex(net_b.id, 2));
+        net_b.is_evaluation_order_up_to_date = false;
+        net_b.build_evaluation_order();
+        net_a.add_connection(None, 0.75, true, NodeIndex(net_a.id, 0), NodeIndex(net_a.id, 2));
+        net_a.is_evaluation_order_up_to_date = false;
+        net_a.build_evaluation_order();
+        net_a.fitness_info = 2.0;
+        net_b.fitness_info = 1.0;
+
+        let node_ids = |net: &Net<f32>| net.nodes.iter().map(|n| n.id).collect::<HashSet<_>>();
+        let connection_ids = |net: &Net<f32>| net.connections.iter().map(|c| c.id).collect::<HashSet<_>>();
+        let shared_connections = &connection_ids(&net_a) & &connection_ids(&net_b);
+        assert_eq!(3, shared_connections.len());
+        // No removals, no structural mutation, and weights left exactly as inherited
+        let params = MutationParams { prob_reset_weight_when_mutating: 0.0, max_weight_change_frac: 0.0, ..MutationParams::weights_only() };
+        let mut winners = HashSet::new();
+        for seed in 0..20 {
+            let child = net_a.cross_into_new_net(&net_b, &params, 1.0, &mut StdRng::seed_from_u64(seed));
+            // The winner is usually the fitter parent, but not always; the child's genes tell which
+            let (winner, loser) = if node_ids(&child) == node_ids(&net_a) { (&net_a, &net_b) } else { (&net_b, &net_a) };
+            winners.insert(winner.id);
+            assert_eq!(node_ids(winner), node_ids(&child));
+            assert_eq!(connection_ids(winner), connection_ids(&child));
+            assert!(connection_ids(loser).difference(&connection_ids(winner)).all(|id| !connection_ids(&child).contains(id)));
+            for c in child.connections.iter() {
+                let weight_of = |net: &Net<f32>| net.map_connection_id_to_index.get(&c.id).map(|&i| net.get_connection(i).weight);
+                if shared_connections.contains(&c.id) {
+                    assert!(Some(c.weight) == weight_of(&net_a) || Some(c.weight) == weight_of(&net_b), "{}", c.weight);
+                } else {
+                    assert_eq!(weight_of(winner), Some(c.weight));
+                }
+            }
+        }
+        assert!(winners.contains(&net_a.id));
+    }
+
     #[test]
     fn test_remove_node() {
         for _ in 0..100 {
@@ -742,29 +1391,35 @@ mod tests {
                 prob_add_connection: 1.0,
                 prob_add_node: 1.0,
                 prob_mutate_activation_function_of_node: 0.0,
+                activation_function_weights: Vec::new(),
                 prob_mutate_weight: 0.0,
                 prob_reset_weight_when_mutating: 0.0,
                 max_weight_change_frac: 0.1,
                 prob_toggle_enabled: 0.0,
                 prob_remove_connection: 0.0,
                 prob_remove_node: 0.0,
+                max_hidden_depth: None,
+                structural_mutations_per_call: 1,
             };
             for _ in 0..5 {
-                net_a.mutate_self(&params, 1.0);
-                net_b.mutate_self(&params, 1.0);
+                net_a.mutate_self(&params, 1.0, &mut thread_rng());
+                net_b.mutate_self(&params, 1.0, &mut thread_rng());
             }
             let params = MutationParams {
                 prob_add_connection: 0.0,
                 prob_add_node: 0.0,
                 prob_mutate_activation_function_of_node: 0.0,
+                activation_function_weights: Vec::new(),
                 prob_mutate_weight: 0.0,
                 prob_reset_weight_when_mutating: 0.0,
                 max_weight_change_frac: 0.1,
                 prob_toggle_enabled: 0.0,
                 prob_remove_connection: 0.0,
                 prob_remove_node: 1.0,
+                max_hidden_depth: None,
+                structural_mutations_per_call: 1,
             };
-            let net_d = net_a.cross_into_new_net(&net_b, &params, 1.0);
+            let net_d = net_a.cross_into_new_net(&net_b, &params, 1.0, &mut thread_rng());
             let nodes_a = net_a.nodes.len();
             let nodes_b = net_b.nodes.len();
             let nodes_d = net_d.nodes.len();
@@ -785,29 +1440,35 @@ mod tests {
                 prob_add_connection: 1.0,
                 prob_add_node: 1.0,
                 prob_mutate_activation_function_of_node: 0.0,
+                activation_function_weights: Vec::new(),
                 prob_mutate_weight: 0.0,
                 prob_reset_weight_when_mutating: 0.0,
                 max_weight_change_frac: 0.1,
                 prob_toggle_enabled: 0.0,
                 prob_remove_connection: 0.0,
                 prob_remove_node: 0.0,
+                max_hidden_depth: None,
+                structural_mutations_per_call: 1,
             };
             for _ in 0..5 {
-                net_a.mutate_self(&params, 1.0);
-                net_b.mutate_self(&params, 1.0);
+                net_a.mutate_self(&params, 1.0, &mut thread_rng());
+                net_b.mutate_self(&params, 1.0, &mut thread_rng());
             }
             let params = MutationParams {
                 prob_add_connection: 0.0,
                 prob_add_node: 0.0,
                 prob_mutate_activation_function_of_node: 0.0,
+                activation_function_weights: Vec::new(),
                 prob_mutate_weight: 0.0,
                 prob_reset_weight_when_mutating: 0.0,
                 max_weight_change_frac: 0.1,
                 prob_toggle_enabled: 0.0,
                 prob_remove_connection: 1.0,
                 prob_remove_node: 0.0,
+                max_hidden_depth: None,
+                structural_mutations_per_call: 1,
             };
-            let net_c = net_a.cross_into_new_net(&net_b, &params, 1.0);
+            let net_c = net_a.cross_into_new_net(&net_b, &params, 1.0, &mut thread_rng());
             let connections_a = net_a.connections.len();
             let connections_b = net_b.connections.len();
             let connections_c = net_c.connections.len();
@@ -817,6 +1478,341 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_adjacency_matrix_single_connection() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::Sigmoid, layer: Layer::Output };
+        let net = Net::<f32>::from_spec(&NetSpec {
+            net_params: NetParams::from_size(2, 1),
+            nodes: vec![input, input, output],
+            connections: vec![
+                ConnectionSpec { from: 1, to: 2, weight:  0.5, is_enabled: true },
+                ConnectionSpec { from: 0, to: 2, weight: -2.0, is_enabled: false },  // Disabled, so ignored
+            ],
+        }).unwrap();
+        let (ids, matrix) = net.to_adjacency_matrix();
+        assert_eq!(ids, net.nodes.iter().map(|n| n.id).collect::<Vec<_>>());
+        assert_eq!(1, matrix.iter().flatten().filter(|&&w| w != 0.0).count());
+        assert_eq!(0.5, matrix[1][2]);
+    }
+
+    #[test]
+    fn test_from_spec_builds_and_validates() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None, layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::None, layer: Layer::Output };
+        let hidden = NodeSpec { activation_function: ActivationFunction::ReLU, layer: Layer::Hidden(1) };
+        let mut spec = NetSpec {
+            net_params: NetParams::from_size(1, 1),
+            nodes: vec![input, output, hidden],
+            connections: vec![
+                ConnectionSpec { from: 0, to: 2, weight: -2.0, is_enabled: true },
+                ConnectionSpec { from: 2, to: 1, weight:  3.0, is_enabled: true },
+            ],
+        };
+        let net = Net::<f32>::from_spec(&spec).unwrap();
+        assert_eq!(Layer::Hidden(1), net.nodes[2].layer);
+        assert_eq!(ActivationFunction::None, net.nodes[1].activation_function);
+        assert_eq!(vec![NodeIndex(net.id, 2), NodeIndex(net.id, 0)], net.nodes.iter().flat_map(|n| &n.input_connections).map(|&c| net.get_connection(c).input_node).collect::<Vec<_>>());
+
+        spec.nodes.push(hidden);
+        spec.connections.push(ConnectionSpec { from: 2, to: 3, weight: 1.0, is_enabled: true });
+        spec.connections.push(ConnectionSpec { from: 3, to: 2, weight: 1.0, is_enabled: false });
+        assert!(Net::<f32>::from_spec(&spec).is_err());     // Cycle
+        spec.connections.pop();
+        spec.connections.push(ConnectionSpec { from: 1, to: 3, weight: 1.0, is_enabled: true });
+        assert!(Net::<f32>::from_spec(&spec).is_err());     // Out of an output
+        spec.nodes.swap(0, 1);
+        spec.connections.clear();
+        assert!(Net::<f32>::from_spec(&spec).is_err());     // Nodes out of order
+    }
+
+    #[test]
+    fn test_compact_string() {
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_hidden = net.add_node(None, ActivationFunction::ReLU, None, 0.0);
+        net.add_connection(None, 1.0, true,  NodeIndex(net.id, 0), ni_hidden);
+        net.add_connection(None, 1.0, true,  ni_hidden, NodeIndex(net.id, 2));
+        net.add_connection(None, 1.0, false, NodeIndex(net.id, 1), NodeIndex(net.id, 2));
+        net.is_evaluation_order_up_to_date = false;
+        net.build_evaluation_order();
+        net.fitness_info = 12.34;
+        let compact = net.to_compact_string();
+        let expected_id = format!("#{}", net.id.0);
+        assert_eq!(vec![expected_id.as_str(), "n4", "c2/3", "h1", "f12.3"], compact.split_whitespace().collect::<Vec<_>>());
+        assert_eq!(compact.find('n'), Net::<f32>::new(NetParams::from_size(2, 1)).to_compact_string().find('n'));
+    }
+
+    #[test]
+    fn test_is_feed_forward() {
+        let mut net = Net::<f32>::new(NetParams::from_size(1, 1));
+        let ni_input  = NodeIndex(net.id, 0);
+        let ni_output = NodeIndex(net.id, 1);
+        let ni_hidden_a = net.add_node(None, ActivationFunction::ReLU, None, 0.0);
+        let ni_hidden_b = net.add_node(None, ActivationFunction::ReLU, None, 0.0);
+        net.add_connection(None, 1.0, true, ni_input, ni_hidden_a);
+        net.add_connection(None, 1.0, true, ni_hidden_a, ni_hidden_b);
+        net.add_connection(None, 1.0, true, ni_hidden_b, ni_output);
+        assert!(net.is_feed_forward());
+        let ci_back = net.add_connection(None, 1.0, false, ni_hidden_b, ni_hidden_a);
+        assert!(net.is_feed_forward());     // Disabled, so doesn't count
+        net.get_connection_mut(ci_back).is_enabled = true;
+        assert!(!net.is_feed_forward());
+    }
+
+    #[test]
+    fn test_unreachable_outputs() {
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 3));
+        let ni_output_a = NodeIndex(net.id, 2);
+        let ni_output_b = NodeIndex(net.id, 3);
+        let ni_output_c = NodeIndex(net.id, 4);
+        let ni_hidden = net.add_node(None, ActivationFunction::LReLU, None, 0.0);
+        net.add_connection(None, 1.0, true,  NodeIndex(net.id, 0), ni_output_a);
+        net.add_connection(None, 1.0, false, NodeIndex(net.id, 1), ni_output_b);  // Disabled, so doesn't count
+        net.add_connection(None, 1.0, true,  NodeIndex(net.id, 1), ni_hidden);
+        net.add_connection(None, 1.0, true,  ni_hidden, ni_output_c);
+        assert_eq!(vec![ni_output_b], net.unreachable_outputs());
+    }
+
+    #[test]
+    fn test_merge_duplicate_connections() {
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_input_a = NodeIndex(net.id, 0);
+        let ni_input_b = NodeIndex(net.id, 1);
+        let ni_output  = NodeIndex(net.id, 2);
+        net.add_connection(None,  0.5,  true,  ni_input_a, ni_output);
+        net.add_connection(None,  1.0,  true,  ni_input_b, ni_output);
+        net.add_connection(None,  0.25, true,  ni_input_a, ni_output);
+        net.add_connection(None, -3.0,  false, ni_input_a, ni_output);   // Disabled, so left alone
+        assert_eq!(1, net.merge_duplicate_connections());
+        assert_eq!(3, net.connections.len());
+        assert_eq!(3, net.get_node(ni_output).input_connections.len());
+        assert_eq!(0.75, net.connections[0].weight);
+        assert_eq!(0, net.merge_duplicate_connections());
+    }
+
+    #[test]
+    fn test_evaluate_matches_hand_computed_forward_pass() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::Sigmoid, layer: Layer::Output };
+        let hidden = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Hidden(1) };
+        let (w1, w2, w3) = (0.5, -1.25, 2.0);
+        let mut net = Net::<f32>::from_spec(&NetSpec {
+            net_params: NetParams::from_size(2, 1),
+            nodes: vec![input, input, output, hidden],
+            connections: vec![
+                ConnectionSpec { from: 0, to: 3, weight: w1, is_enabled: true },
+                ConnectionSpec { from: 1, to: 3, weight: w2, is_enabled: true },
+                ConnectionSpec { from: 3, to: 2, weight: w3, is_enabled: true },
+            ],
+        }).unwrap();
+        let (x1, x2) = (0.8, -0.4);
+        net.build_evaluation_order();
+        net.set_inputs(&[x1, x2]);
+        net.evaluate();
+        let expected = 1.0 / (1.0 + (-(w3 * (w1 * x1 + w2 * x2))).exp());
+        assert!((net.get_outputs()[0] - expected).abs() < 1e-6, "{} vs {expected}", net.get_outputs()[0]);
+    }
+
+    #[test]
+    fn test_toggling_connection_changes_output() {
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_output = NodeIndex(net.id, 2);
+        net.get_node_mut(ni_output).activation_function = ActivationFunction::None;
+        net.add_connection(None, 0.5, true, NodeIndex(net.id, 0), ni_output);
+        let ci_b = net.add_connection(None, 2.0, true, NodeIndex(net.id, 1), ni_output);
+        let output_of = |net: &mut Net<f32>| {
+            net.build_evaluation_order();
+            net.set_inputs(&[1.0, -1.0]);
+            net.evaluate();
+            net.get_outputs()[0]
+        };
+        assert_eq!(-1.5, output_of(&mut net));
+        net.get_connection_mut(ci_b).is_enabled = false;
+        // Toggling doesn't rebuild the evaluation order, so this is evaluate() itself skipping it
+        assert_eq!(0.5, output_of(&mut net));
+        net.get_connection_mut(ci_b).is_enabled = true;
+        assert_eq!(-1.5, output_of(&mut net));
+    }
+
+    #[test]
+    fn test_prune_disabled_connections() {
+        let mut net = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_input_a = NodeIndex(net.id, 0);
+        let ni_input_b = NodeIndex(net.id, 1);
+        let ni_output  = NodeIndex(net.id, 2);
+        let ni_hidden  = net.add_node(None, ActivationFunction::Sigmoid, None, 0.0);
+        net.add_connection(None,  0.5, false, ni_input_a, ni_output);     // Split by ni_hidden
+        net.add_connection(None,  0.5, true,  ni_input_a, ni_hidden);
+        net.add_connection(None,  1.0, true,  ni_hidden,  ni_output);
+        net.add_connection(None, -2.0, false, ni_hidden,  ni_output);
+        net.add_connection(None,  1.5, true,  ni_input_b, ni_output);
+        net.is_evaluation_order_up_to_date = false;
+        net.build_evaluation_order();
+        net.set_inputs(&[1.0, -1.0]);
+        net.evaluate();
+        let outputs = net.get_outputs();
+
+        let mut net_kept = net.clone();
+        assert_eq!(1, net_kept.prune_disabled_connections(true));
+        assert!(!net_kept.connections[0].is_enabled);
+
+        let enabled_ids = net.connections.iter().filter(|c| c.is_enabled).map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(2, net.prune_disabled_connections(false));
+        assert_eq!(enabled_ids, net.connections.iter().map(|c| c.id).collect::<Vec<_>>());
+        assert_eq!(0, net.prune_disabled_connections(false));
+        net.set_inputs(&[1.0, -1.0]);
+        net.evaluate();
+        assert_eq!(outputs, net.get_outputs());
+    }
+
+    #[test]
+    fn test_quantized_weights_round_trip() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::Sigmoid, layer: Layer::Output };
+        let hidden = NodeSpec { activation_function: ActivationFunction::Tanh,    layer: Layer::Hidden(1) };
+        let mut net = Net::<f32>::from_spec(&NetSpec {
+            net_params: NetParams::from_size(2, 1),
+            nodes: vec![input, input, output, hidden],
+            connections: vec![
+                ConnectionSpec { from: 0, to: 3, weight:  0.123_456_7, is_enabled: true },
+                ConnectionSpec { from: 1, to: 3, weight: -1.987_654_3, is_enabled: true },
+                ConnectionSpec { from: 3, to: 2, weight:  2.468_135_7, is_enabled: true },
+                ConnectionSpec { from: 1, to: 2, weight:  0.333_333_3, is_enabled: true },
+            ],
+        }).unwrap();
+        let inputs = [0.7, -0.4];
+        net.set_inputs(&inputs);
+        net.evaluate();
+        let outputs = net.get_outputs();
+
+        let mut net_quantized = net.clone();
+        net_quantized.quantize_weights(3);
+        let json = serde_json::to_string(&net_quantized).unwrap();
+        assert!(json.len() < serde_json::to_string(&net).unwrap().len());
+        let mut net_loaded: Net<f32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(vec![0.123, -1.988, 2.468, 0.333], net_loaded.connections.iter().map(|c| c.weight).collect::<Vec<_>>());
+        net_loaded.build_evaluation_order();
+        net_loaded.set_inputs(&inputs);
+        net_loaded.evaluate();
+        for (a, b) in outputs.iter().zip(net_loaded.get_outputs()) {
+            assert!((a - b).abs() < 1e-3, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn test_layer_sizes() {
+        let input  = NodeSpec { activation_function: ActivationFunction::None, layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::None, layer: Layer::Output };
+        let hidden = NodeSpec { activation_function: ActivationFunction::ReLU, layer: Layer::Hidden(1) };
+        let net = Net::<f32>::from_spec(&NetSpec {
+            net_params: NetParams::from_size(1, 1),
+            nodes: vec![input, output, hidden, hidden, hidden],
+            connections: vec![
+                ConnectionSpec { from: 0, to: 2, weight: 1.0, is_enabled: true },
+                ConnectionSpec { from: 0, to: 3, weight: 1.0, is_enabled: true },
+                ConnectionSpec { from: 2, to: 4, weight: 1.0, is_enabled: true },
+                ConnectionSpec { from: 4, to: 1, weight: 1.0, is_enabled: true },
+                ConnectionSpec { from: 3, to: 1, weight: 1.0, is_enabled: true },
+            ],
+        }).unwrap();
+        assert_eq!(Layer::Hidden(2), net.nodes[4].layer);
+        assert_eq!(BTreeMap::from([(1, 2), (2, 1)]), net.layer_sizes());
+    }
+
+    #[test]
+    fn test_describe_outputs() {
+        let mut net = Net::<f32>::new(NetParams::from_size(1, 2));
+        net.add_connection(None, 1.0, true, NodeIndex(net.id, 0), NodeIndex(net.id, 1));
+        net.build_evaluation_order();
+        net.set_inputs(&[2.0]);
+        net.evaluate();
+        let outputs = net.get_outputs();
+        assert_eq!(vec![("0".to_string(), outputs[0]), ("1".to_string(), outputs[1])], net.describe_outputs());
+        net.net_params.output_names = Some(&["Left", "Right"]);
+        assert_eq!(vec![("Left".to_string(), outputs[0]), ("Right".to_string(), outputs[1])], net.describe_outputs());
+    }
+
+    #[test]
+    fn test_copy_weights_from() {
+        let mut net_a = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_output = NodeIndex(net_a.id, 2);
+        net_a.add_connection(None, 0.5, true, NodeIndex(net_a.id, 0), ni_output);
+        let mut net_b = net_a.clone_as_new_net();
+        net_b.connections[0].weight = -1.5;
+        net_b.add_connection(None, 2.0, true, NodeIndex(net_b.id, 1), NodeIndex(net_b.id, 2));
+        net_a.add_connection(None, 0.25, true, NodeIndex(net_a.id, 1), ni_output);
+        net_a.copy_weights_from(&net_b);
+        assert_eq!(vec![-1.5, 0.25], net_a.connections.iter().map(|c| c.weight).collect::<Vec<_>>());
+        assert_eq!(vec![-1.5, 2.0], net_b.connections.iter().map(|c| c.weight).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_random_with_same_seed_same_net() {
+        let mut_params = MutationParams { prob_add_connection: 1.0, ..MutationParams::aggressive() };
+        let random_net = |seed| Net::<f32>::random_with(NetParams::from_size(6, 3), &mut_params, &mut StdRng::seed_from_u64(seed));
+        let (net_a, net_b) = (random_net(42), random_net(42));
+        assert_ne!(net_a.id, net_b.id);
+        assert!(net_a.connection_count() > 0);
+        assert_eq!(net_a.to_adjacency_matrix().1, net_b.to_adjacency_matrix().1);
+        assert_eq!(net_a.layer_sizes(), net_b.layer_sizes());
+        let activation_functions = |net: &Net<f32>| net.nodes.iter().map(|n| n.activation_function).collect::<Vec<_>>();
+        assert_eq!(activation_functions(&net_a), activation_functions(&net_b));
+    }
+
+    #[test]
+    fn test_try_get_rejects_foreign_indices() {
+        let mut net_a = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ci = net_a.add_connection(None, 0.5, true, NodeIndex(net_a.id, 0), NodeIndex(net_a.id, 2));
+        let net_b = net_a.clone_as_new_net();
+        assert_eq!(Some(NodeIndex(net_a.id, 2)), net_a.try_get_node(NodeIndex(net_a.id, 2)).map(|n| n.index));
+        assert_eq!(Some(0.5), net_a.try_get_connection(ci).map(|c| c.weight));
+        assert!(net_b.try_get_node(NodeIndex(net_a.id, 2)).is_none());
+        assert!(net_b.try_get_connection(ci).is_none());
+        assert!(net_a.try_get_node(NodeIndex(net_a.id, 3)).is_none());
+        assert!(net_a.try_get_connection(ConnectionIndex(net_a.id, 1)).is_none());
+    }
+
+    #[test]
+    fn test_compatibility_distance() {
+        let mut net_a = Net::<f32>::new(NetParams::from_size(2, 1));
+        let ni_output = NodeIndex(net_a.id, 2);
+        net_a.add_connection(None, 0.5, true, NodeIndex(net_a.id, 0), ni_output);
+        let mut net_b = net_a.clone();
+        assert_eq!(0.0, net_a.compatibility_distance(&net_b));
+        net_b.connections[0].weight = 1.5;
+        assert!((net_a.compatibility_distance(&net_b) - 0.4).abs() < 1e-6);
+        net_b.add_connection(None, 1.0, true, NodeIndex(net_b.id, 1), ni_output);
+        // One of two connections is disjoint, and the matching one differs by 1.0
+        assert!((net_a.compatibility_distance(&net_b) - 0.9).abs() < 1e-6);
+        assert_eq!(net_a.compatibility_distance(&net_b), net_b.compatibility_distance(&net_a));
+    }
+
+    #[test]
+    fn test_output_fed_only_by_unreachable_nodes() {
+        let mut net = Net::<f32>::new(NetParams::from_size(1, 1));
+        let ni_input  = NodeIndex(net.id, 0);
+        let ni_output = NodeIndex(net.id, 1);
+        // HA's only input is disabled, and HB has none: neither carries anything from the input
+        let ni_ha = net.add_node(None, ActivationFunction::LReLU, None, 0.0);
+        net.add_connection(None, 1.0, false, ni_input, ni_ha);
+        net.add_connection(None, 1.0, true, ni_ha, ni_output);
+        let ni_hb = net.add_node(None, ActivationFunction::LReLU, None, 0.0);
+        net.add_connection(None, 1.0, true, ni_hb, ni_output);
+        net.build_evaluation_order();
+        net.verify_invariants();
+        assert_eq!(Layer::Output, net.get_node(ni_output).layer);
+        assert_eq!(vec![ni_output], net.unreachable_outputs());
+
+        let output_for = |net: &mut Net<f32>, input: f32| {
+            net.set_inputs(&[input]);
+            net.evaluate();
+            net.get_outputs()[0]
+        };
+        let output = output_for(&mut net, 0.0);
+        assert!(!output.is_nan());
+        assert_eq!(output, output_for(&mut net, 1.0));
+    }
+
     #[test]
     fn test_unconnected_hidden_node() {
         let mut net_a = Net::<f32>::new(NetParams::from_size(1, 1));
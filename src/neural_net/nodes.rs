@@ -13,6 +13,16 @@ impl NodeId {
     pub fn new_unique() -> NodeId {
         NodeId(NODE_ID_NEXT.fetch_add(1, Ordering::SeqCst))
     }
+
+    /// The number the next `new_unique()` will use.
+    pub(super) fn next_unused() -> usize {
+        NODE_ID_NEXT.load(Ordering::SeqCst)
+    }
+
+    /// Makes `new_unique()` hand out `next` or later from now on; never moves backward.
+    pub(super) fn reserve_before(next: usize) {
+        NODE_ID_NEXT.fetch_max(next, Ordering::SeqCst);
+    }
 }
 
 impl fmt::Display for NodeId {
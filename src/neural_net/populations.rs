@@ -1,7 +1,8 @@
 use std::cmp::Ordering;
 
 use bevy::utils::hashbrown::HashSet;
-use rand::{thread_rng, Rng};
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
 
 use crate::neural_net::nets::NetId;
 
@@ -13,8 +14,25 @@ pub struct PopulationParams {
     pub population_size: usize,
     pub mutation_params: MutationParams,
     pub net_params: NetParams,
+    pub seed: Option<u64>,      // None to seed from entropy
+    pub mutated_elite_count: usize, // How many of the ELITE_COUNT fittest also get a mutated copy
+    pub asexual_only: bool,     // Fill with mutated clones instead of crossovers, e.g. to measure what crossover adds
+    pub cloned_fraction: f64,   // 0.25; the fraction of population_size carried forward unchanged (beyond the elites), chosen by rank
+    pub fitness_sharing: Option<FitnessSharing>, // None; else nets are ranked by fitness shared with their niche
 }
 
+/// Fitness sharing: before ranking, each net's fitness is divided by its niche count, i.e. how
+/// many nets are within `threshold` of it by `Net::compatibility_distance()` (itself included), so
+/// a crowd of near-identical nets doesn't crowd out everything else.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FitnessSharing {
+    pub threshold: f32,
+    pub sample: usize,          // Other nets compared per net to estimate its niche count, rather than all O(n²) pairs
+}
+
+/// How many of the fittest nets are carried unchanged into the next generation.
+pub const ELITE_COUNT: usize = 4;
+
 pub trait FitnessInfo : Clone + Default + std::fmt::Debug {
     fn get_fitness(&self) -> f32;
     fn set_fitness(&mut self, new: f32);
@@ -29,16 +47,27 @@ impl FitnessInfo for f32 {
 pub struct Population<Fit> where Fit: FitnessInfo {
     pub nets: Vec<Net<Fit>>,
     pub population_params: PopulationParams,
+    rng: StdRng,                // All randomness in creating and breeding nets comes from here
+    generation: usize,          // How many times create_next_generation() has run
 }
 
 impl <Fit> Population<Fit> where Fit: FitnessInfo {
     pub fn new(meta: PopulationParams) -> Self {
+        let rng = match meta.seed {
+            None => StdRng::from_entropy(),
+            Some(seed) => StdRng::seed_from_u64(seed),
+        };
         Self {
             nets: Vec::<Net<Fit>>::new(),
             population_params: meta,
+            rng,
+            generation: 0,
         }
     }
 
+    /// How many generations have been bred so far, i.e. 0 for the initial population.
+    pub fn generation(&self) -> usize { self.generation }
+
     pub fn run_one_generation(&mut self, mutation_multipier: f64, fitness_of_net: impl FnMut(&mut Net<Fit>) -> Fit) {
         self.create_initial_population();
         self.evaluate_population(fitness_of_net);
@@ -47,9 +76,13 @@ impl <Fit> Population<Fit> where Fit: FitnessInfo {
     }
 
     pub fn create_initial_population(&mut self) {
-        while self.nets.len() < self.population_params.population_size {
-            let mut net = Net::new(self.population_params.net_params.clone());
-            net.mutate_self(&self.population_params.mutation_params, 1.0);
+        self.fill_with_random_nets(self.population_params.population_size);
+    }
+
+    /// Adds freshly mutated nets until there are at least `count` nets.
+    pub fn fill_with_random_nets(&mut self, count: usize) {
+        while self.nets.len() < count {
+            let net = Net::random_with(self.population_params.net_params.clone(), &self.population_params.mutation_params, &mut self.rng);
             assert!(net.is_evaluation_order_up_to_date);
             self.nets.push(net);
         }
@@ -62,10 +95,18 @@ impl <Fit> Population<Fit> where Fit: FitnessInfo {
     }
 
     pub fn create_next_generation(&mut self, mutation_multiplier: f64) {
-        // Sort population by fitness
-        self.nets.sort_by(|a,b| Ordering::reverse(a.fitness_info.get_fitness().partial_cmp(&b.fitness_info.get_fitness()).unwrap()));
-        assert!(self.nets[0].fitness_info.get_fitness() >= self.nets[self.nets.len() - 1].fitness_info.get_fitness());
-        assert!(self.nets[0].fitness_info.get_fitness() >= self.nets[1].fitness_info.get_fitness());
+        // Sort population by (shared, if fitness_sharing) fitness, then by age (older NetId
+        // first), so ranks don't depend on the order the nets happened to be in.  Elites and
+        // parents alike are then chosen by rank.
+        let rank_fitnesses = match self.population_params.fitness_sharing {
+            None => self.nets.iter().map(|net| net.fitness_info.get_fitness()).collect::<Vec<_>>(),
+            Some(sharing) => self.shared_fitnesses(sharing),
+        };
+        let mut ranked = rank_fitnesses.into_iter().zip(self.nets.drain(..)).collect::<Vec<_>>();
+        ranked.sort_by(|(fa, a), (fb, b)| Ordering::reverse(fa.partial_cmp(fb).unwrap()).then_with(|| a.id.cmp(&b.id)));
+        assert!(ranked[0].0 >= ranked[ranked.len() - 1].0);
+        assert!(ranked[0].0 >= ranked[1].0);
+        self.nets = ranked.into_iter().map(|(_, net)| net).collect();
         let mut nets_already_chosen = HashSet::<NetId>::with_capacity(self.nets.len());
         //for i in 0..self.nets.len() {
         //    let net = &self.nets[i];
@@ -73,19 +114,35 @@ impl <Fit> Population<Fit> where Fit: FitnessInfo {
         //}
 
         // Forward propigate most fit nets
-        let mut nets_new = Vec::<Net<Fit>>::with_capacity(self.nets.len());
-        for i in 0..4 {
+        let population_size = self.population_params.population_size;
+        let mut nets_new = Vec::<Net<Fit>>::with_capacity(population_size);
+        for i in 0..ELITE_COUNT.min(population_size).min(self.nets.len()) {
             nets_new.push(self.nets[i].clone());
             nets_already_chosen.insert(self.nets[i].id);
         }
 
-        // Choose 25% of population randomly from current population, biased by their fitness
-        // ranking.
-        let percent_25 = (self.population_params.population_size as f32 * 0.25).round() as usize;
-        let target = 4 + percent_25;
+        // Also explore around the most fit nets, with mutated copies of them
+        let mutated_elite_count = self.population_params.mutated_elite_count.min(nets_new.len()).min(population_size - nets_new.len());
+        for i in 0..mutated_elite_count {
+            let mut net = self.nets[i].clone_as_new_net();
+            net.mutate_self(&self.population_params.mutation_params, mutation_multiplier, &mut self.rng);
+            nets_new.push(net);
+        }
+
+        // Parents come from the fittest population_size nets, so a population inflated (e.g. by a
+        // resurrection) keeps the usual selection pressure rather than diluting it with extra ranks
+        let pool = self.nets.len().min(population_size);
+
+        // Choose cloned_fraction of population randomly from current population, biased by their
+        // fitness ranking.  Small populations can't fit them all after the elites, and (with
+        // repeated ids, e.g. from a resurrection) there may not be that many nets left to choose.
+        let cloned_count = (population_size as f64 * self.population_params.cloned_fraction).round() as usize;
+        let unchosen_count = self.nets[..pool].iter().map(|n| n.id).filter(|id| !nets_already_chosen.contains(id)).collect::<HashSet<_>>().len();
+        let target = (nets_new.len() + cloned_count.min(unchosen_count)).min(population_size);
         let mut rechosen_count = 0_usize;
         while nets_new.len() < target {
-            let net_chosen = &self.nets[self.choose()];
+            let i = self.choose(pool);
+            let net_chosen = &self.nets[i];
             let is_already_chosen = nets_already_chosen.contains(&net_chosen.id);
             let new_net = net_chosen.clone();
             if is_already_chosen { rechosen_count += 1; continue; } // new_net.mutate_self(&self.population_params.mutation_params, mutation_multiplier * 2.0); }
@@ -94,21 +151,316 @@ impl <Fit> Population<Fit> where Fit: FitnessInfo {
         }
         //println!("Rechosen: {rechosen_count} out of {target}");
 
-        // Fill out population by randomly choosing nets to cross proportionally by fitness
-        while nets_new.len() < self.population_params.population_size {
-            let net_chosen_a = &self.nets[self.choose()];
-            let net_chosen_b = &self.nets[self.choose()];
+        // Fill out population by randomly choosing nets to cross proportionally by fitness (or,
+        // when asexual_only, to clone and mutate)
+        while nets_new.len() < population_size && self.population_params.asexual_only {
+            let i = self.choose(pool);
+            let mut net_new = self.nets[i].clone_as_new_net();
+            net_new.mutate_self(&self.population_params.mutation_params, mutation_multiplier, &mut self.rng);
+            nets_new.push(net_new);
+        }
+        while nets_new.len() < population_size {
+            let (a, b) = (self.choose(pool), self.choose(pool));
+            let net_chosen_a = &self.nets[a];
+            let net_chosen_b = &self.nets[b];
             if std::ptr::addr_eq(net_chosen_a, net_chosen_b) { continue; }  // Skip if same
-            let net_new = net_chosen_a.cross_into_new_net(net_chosen_b, &self.population_params.mutation_params, mutation_multiplier);
+            let net_new = net_chosen_a.cross_into_new_net(net_chosen_b, &self.population_params.mutation_params, mutation_multiplier, &mut self.rng);
             nets_new.push(net_new);
         }
         self.nets = nets_new;
+        self.generation += 1;
+    }
+
+    /// Keeps only the `n` fittest nets (fittest first), e.g. after injecting extra nets.
+    pub fn retain_best(&mut self, n: usize) {
+        self.nets.sort_by(|a, b| b.fitness_info.get_fitness().total_cmp(&a.fitness_info.get_fitness()));
+        self.nets.truncate(n);
+    }
+
+    /// Removes nets whose genome (see `Net::structure_hash()`) duplicates an earlier net's, then,
+    /// if `refill`, tops the population back up with random nets.  Returns how many were removed.
+    pub fn cull_identical(&mut self, refill: bool) -> usize {
+        let count_before = self.nets.len();
+        let mut hashes_seen = HashSet::<u64>::with_capacity(self.nets.len());
+        self.nets.retain(|net| hashes_seen.insert(net.structure_hash()));
+        let removed = count_before - self.nets.len();
+        if refill { self.fill_with_random_nets(self.population_params.population_size); }
+        removed
     }
 
-    fn choose(&self) -> usize {
-        let rand = thread_rng().gen::<f32>();
+    /// Each net's fitness shared with its niche: divided by its niche count (or, for a negative
+    /// fitness, multiplied, so crowding still only ever hurts).  The niche count is estimated from
+    /// `sharing.sample` randomly chosen other nets, scaled up to the whole population.
+    fn shared_fitnesses(&mut self, sharing: FitnessSharing) -> Vec<f32> {
+        let n = self.nets.len();
+        let mut shared = Vec::with_capacity(n);
+        for i in 0..n {
+            let others = n - 1;
+            let sample = sharing.sample.min(others);
+            let mut within = 0;
+            for k in 0..sample {
+                // Every other net when there are few enough, else a random one
+                let offset = if sample == others { k + 1 } else { self.rng.gen_range(1..n) };
+                let j = (i + offset) % n;
+                if self.nets[i].compatibility_distance(&self.nets[j]) < sharing.threshold { within += 1; }
+            }
+            let niche_count = if sample == 0 { 1.0 } else { 1.0 + within as f32 * others as f32 / sample as f32 };
+            let fitness = self.nets[i].fitness_info.get_fitness();
+            shared.push(if fitness >= 0.0 { fitness / niche_count } else { fitness * niche_count });
+        }
+        shared
+    }
+
+    /// A cheap proxy for genetic diversity: the mean `Net::compatibility_distance()` of `sample`
+    /// randomly chosen pairs of distinct nets (rather than all O(n²) pairs).
+    pub fn average_compatibility_distance(&self, sample: usize) -> f32 {
+        if self.nets.len() < 2 || sample == 0 { return 0.0; }
+        let mut sum = 0.0;
+        for _ in 0..sample {
+            let a = thread_rng().gen_range(0..self.nets.len());
+            let b = (a + thread_rng().gen_range(1..self.nets.len())) % self.nets.len();
+            sum += self.nets[a].compatibility_distance(&self.nets[b]);
+        }
+        sum / sample as f32
+    }
+
+    /// The index of a net among the first `pool` (i.e. fittest, once sorted), favoring the fitter.
+    fn choose(&mut self, pool: usize) -> usize {
+        let rand = self.rng.gen::<f32>();
         let sq = rand * rand;   // more likely to choose values close to 0.0 than 1.0
-        let index = (sq * self.nets.len() as f32).round() as usize;
-        index.clamp(0, self.nets.len() - 1)
+        let index = (sq * pool as f32).round() as usize;
+        index.clamp(0, pool - 1)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn population_params() -> PopulationParams {
+        PopulationParams {
+            population_size: 10,
+            mutation_params: MutationParams {
+                prob_add_connection: 1.0,
+                prob_add_node: 0.5,
+                prob_mutate_activation_function_of_node: 0.0,
+                activation_function_weights: Vec::new(),
+                prob_mutate_weight: 0.0,
+                prob_reset_weight_when_mutating: 0.0,
+                max_weight_change_frac: 0.1,
+                prob_toggle_enabled: 0.0,
+                prob_remove_connection: 0.0,
+                prob_remove_node: 0.0,
+                max_hidden_depth: None,
+                structural_mutations_per_call: 1,
+            },
+            net_params: NetParams { input_count: 4, input_names: None, output_count: 2, output_names: None },
+            seed: None,
+            mutated_elite_count: 0,
+            asexual_only: false,
+            cloned_fraction: 0.25,
+            fitness_sharing: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_nets_have_no_diversity() {
+        let mut population = Population::<f32>::new(population_params());
+        population.create_initial_population();
+        let net = population.nets[0].clone();
+        population.nets.fill(net);
+        assert!(population.average_compatibility_distance(50) < 1e-6);
+    }
+
+    #[test]
+    fn test_independent_nets_have_diversity() {
+        let mut population = Population::<f32>::new(population_params());
+        population.create_initial_population();
+        assert!(population.average_compatibility_distance(50) > 0.0);
+    }
+
+    #[test]
+    fn test_fitness_sharing_damps_crowded_nets() {
+        let sharing = FitnessSharing { threshold: 0.5, sample: 4 };
+        let params = PopulationParams { seed: Some(1712), fitness_sharing: Some(sharing), ..population_params() };
+        let mut population = Population::<f32>::new(params);
+        population.create_initial_population();
+        // Nine copies of one net, and one independently random (so, disjoint) net
+        let crowd = population.nets[0].clone();
+        let loner = population.nets[1].clone();
+        population.nets = (0..9).map(|_| crowd.clone_as_new_net()).chain([loner.clone()]).collect();
+        for net in population.nets.iter_mut() { net.fitness_info = 10.0; }
+        assert!(crowd.compatibility_distance(&loner) >= sharing.threshold);
+
+        // Comparing against every other net, each copy's niche is all nine copies
+        let shared = population.shared_fitnesses(FitnessSharing { sample: 100, ..sharing });
+        assert_eq!(10.0, shared[9]);
+        assert!(shared[..9].iter().all(|&f| (f - 10.0 / 9.0).abs() < 1e-5), "{shared:?}");
+        // Sampled, the estimate is rougher, but the copies are still damped and the loner isn't
+        let shared = population.shared_fitnesses(sharing);
+        assert_eq!(10.0, shared[9]);
+        assert!(shared[..9].iter().all(|&f| f < 10.0), "{shared:?}");
+
+        // Ranked by shared fitness, the loner comes first despite its (unshared) tie
+        population.create_next_generation(1.0);
+        assert_eq!(loner.id, population.nets[0].id);
+    }
+
+    #[test]
+    fn test_cull_identical_keeps_one_copy() {
+        let mut population = Population::<f32>::new(population_params());
+        population.create_initial_population();
+        let original = population.nets[0].clone();
+        population.nets.extend([original.clone_as_new_net(), original.clone_as_new_net()]);
+        let copies = |population: &Population<f32>| population.nets.iter().filter(|n| n.structure_hash() == original.structure_hash()).count();
+        assert_eq!(3, copies(&population));
+
+        assert_eq!(2, population.cull_identical(false));
+        assert_eq!(1, copies(&population));
+        assert_eq!(10, population.nets.len());
+        population.nets.push(original.clone_as_new_net());
+        assert_eq!(1, population.cull_identical(true));
+        assert_eq!(10, population.nets.len());
+    }
+
+    #[test]
+    fn test_retain_best_keeps_fittest() {
+        let mut population = Population::<f32>::new(population_params());
+        population.create_initial_population();
+        for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = i as f32; }
+        let mut extra = population.nets[0].clone();
+        extra.fitness_info = 100.0;
+        population.nets.extend([extra.clone(), extra.clone(), extra]);
+        population.retain_best(population.population_params.population_size);
+        assert_eq!(10, population.nets.len());
+        let fitnesses = population.nets.iter().map(|n| n.fitness_info).collect::<Vec<_>>();
+        assert_eq!(vec![100.0, 100.0, 100.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0], fitnesses);
+    }
+
+    #[test]
+    fn test_mutated_elites_next_to_pristine_ones() {
+        let params = PopulationParams { mutated_elite_count: 1, seed: Some(1), ..population_params() };
+        let mut population = Population::<f32>::new(params);
+        population.create_initial_population();
+        for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = -(i as f32); }
+        let best = population.nets[0].clone();
+        population.create_next_generation(1.0);
+
+        let pristine = &population.nets[0];
+        assert_eq!(best.id, pristine.id);
+        assert_eq!(best.to_adjacency_matrix(), pristine.to_adjacency_matrix());
+        let mutated = &population.nets[ELITE_COUNT];
+        assert_ne!(best.id, mutated.id);
+        let node_ids = |net: &Net<f32>| net.to_adjacency_matrix().0;
+        assert!(node_ids(&best).iter().all(|id| node_ids(mutated).contains(id)));
+        assert_ne!(best.to_adjacency_matrix(), mutated.to_adjacency_matrix());
+    }
+
+    #[test]
+    fn test_asexual_children_copy_a_single_parent() {
+        let params = PopulationParams { population_size: 20, asexual_only: true, ..population_params() };
+        let mut population = Population::<f32>::new(params);
+        population.create_initial_population();
+        // Parents share genes but not weights, so crossing any two would mix their weights
+        let mut base = population.nets[0].clone();
+        for _ in 0..10 { base.mutate_self(&population.population_params.mutation_params, 1.0, &mut thread_rng()); }
+        for net in population.nets.iter_mut() {
+            *net = base.clone_as_new_net();
+            net.jitter_weights(1.0, &mut thread_rng());
+        }
+        // Without mutation, each child must be an exact copy of its one parent
+        let mutation_params = &mut population.population_params.mutation_params;
+        (mutation_params.prob_add_connection, mutation_params.prob_add_node, mutation_params.max_weight_change_frac) = (0.0, 0.0, 0.0);
+        for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = i as f32; }
+        let parents = population.nets.iter().map(|n| n.to_adjacency_matrix()).collect::<Vec<_>>();
+        population.create_next_generation(1.0);
+        assert_eq!(20, population.nets.len());
+        assert!(population.nets.iter().all(|child| parents.contains(&child.to_adjacency_matrix())));
+    }
+
+    #[test]
+    fn test_next_generation_is_exactly_population_size() {
+        let base = PopulationParams { population_size: 40, ..population_params() };
+        let variants = [
+            base.clone(),
+            PopulationParams { mutated_elite_count: ELITE_COUNT, ..base.clone() },
+            PopulationParams { asexual_only: true, ..base.clone() },
+            // The elites and their mutated copies alone would crowd out the cloned ones
+            PopulationParams { population_size: 8, mutated_elite_count: ELITE_COUNT, ..base.clone() },
+        ];
+        for params in variants {
+            for extra_nets in [0, 15] {
+                let mut population = Population::<f32>::new(params.clone());
+                population.create_initial_population();
+                // As if grown by a resurrection, including nets that share an id
+                let resurrected = population.nets[0].clone();
+                population.nets.extend((0..extra_nets).map(|_| resurrected.clone()));
+                for generation in 0..3 {
+                    for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = i as f32; }
+                    population.create_next_generation(1.0);
+                    assert_eq!(params.population_size, population.nets.len(), "{params:?}, extra_nets={extra_nets}, generation={generation}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_oversized_population_breeds_in_proportion() {
+        for cloned_fraction in [0.25, 0.5] {
+            let params = PopulationParams { population_size: 40, cloned_fraction, ..population_params() };
+            let mut population = Population::<f32>::new(params);
+            population.fill_with_random_nets(100);
+            for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = -(i as f32); }
+            let top_ids = population.nets[..40].iter().map(|n| n.id).collect::<Vec<_>>();
+            let all_ids = population.nets.iter().map(|n| n.id).collect::<Vec<_>>();
+            population.create_next_generation(1.0);
+            assert_eq!(40, population.nets.len());
+            let cloned = population.nets.iter().filter(|n| all_ids.contains(&n.id)).collect::<Vec<_>>();
+            assert_eq!(ELITE_COUNT + (40.0 * cloned_fraction) as usize, cloned.len());
+            assert!(cloned.iter().all(|n| top_ids.contains(&n.id)));
+        }
+    }
+
+    #[test]
+    fn test_generation_counts_breedings() {
+        let mut population = Population::<f32>::new(population_params());
+        population.create_initial_population();
+        assert_eq!(0, population.generation());
+        for expected in 1..=3 {
+            population.create_next_generation(1.0);
+            assert_eq!(expected, population.generation());
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_first_generation() {
+        let params = PopulationParams { seed: Some(1234), ..population_params() };
+        let mut population_a = Population::<f32>::new(params.clone());
+        let mut population_b = Population::<f32>::new(params);
+        for population in [&mut population_a, &mut population_b] {
+            population.create_initial_population();
+            for (i, net) in population.nets.iter_mut().enumerate() { net.fitness_info = i as f32; }
+            population.create_next_generation(1.0);
+        }
+        // Ids are globally unique, so compare everything else
+        let weights_of = |population: &Population<f32>| population.nets.iter().map(|n| n.to_adjacency_matrix().1).collect::<Vec<_>>();
+        assert_eq!(weights_of(&population_a), weights_of(&population_b));
+    }
+
+    #[test]
+    fn test_equal_fitnesses_ranked_by_id() {
+        let params = PopulationParams { seed: Some(99), ..population_params() };
+        let mut population_a = Population::<f32>::new(params.clone());
+        let mut population_b = Population::<f32>::new(params);
+        population_a.create_initial_population();
+        population_b.create_initial_population();
+        // Same nets, same (tied) fitnesses, but listed in a different order
+        population_b.nets.reverse();
+        for population in [&mut population_a, &mut population_b] {
+            population.create_next_generation(1.0);
+        }
+        let weights_of = |population: &Population<f32>| population.nets.iter().map(|n| n.to_adjacency_matrix().1).collect::<Vec<_>>();
+        assert_eq!(weights_of(&population_a), weights_of(&population_b));
     }
 }
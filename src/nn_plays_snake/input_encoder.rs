@@ -0,0 +1,155 @@
+//! Turns a game's state into the Net's inputs.  The feature names are generated alongside the
+//! features, so `NetParams::input_names` can't drift from what the Net is actually fed.
+
+use crate::snake_game::{CellKind, Direction, SnakeGame};
+
+use super::BoardTransform;
+
+const DIRECTIONS: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+/// Which features the Net sees.  Each mode starts with the `Standard` features, so a mode's
+/// extra features always come after them.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum InputEncoder {
+    /// NESW distances to the wall and to the snake, x,y offset from the apple, snake length, and a bias.
+    #[default]
+    Standard,
+    /// `Standard`, plus whether moving NESW would crash right away.
+    WithDangers,
+    /// `WithDangers`, plus whether NESW is the tail's cell, which it vacates this move (unless growing).
+    WithTail,
+}
+
+impl InputEncoder {
+    pub const ALL: [InputEncoder; 3] = [InputEncoder::Standard, InputEncoder::WithDangers, InputEncoder::WithTail];
+    /// The usual (min, max) for `encode()` to clamp the inputs to.
+    pub const DEFAULT_RANGE: (f32, f32) = (-1.0, 1.0);
+
+    /// One name per input, in the order `encode()` produces them.
+    pub fn feature_names(&self) -> Vec<&'static str> {
+        let mut names = vec![
+            "WallN", "WallE", "WallS", "WallW",
+            "SnakeN", "SnakeE", "SnakeS", "SnakeW",
+            "AppleDistX", "AppleDistY",
+            "SnakeLen",
+            "1.0",
+        ];
+        if matches!(self, InputEncoder::WithDangers | InputEncoder::WithTail) {
+            names.extend(["DangerN", "DangerE", "DangerS", "DangerW"]);
+        }
+        if *self == InputEncoder::WithTail {
+            names.extend(["TailN", "TailE", "TailS", "TailW"]);
+        }
+        names
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.feature_names().len()
+    }
+
+    /// The (normalized) inputs for `game`, with the board seen through `transform`, each clamped
+    /// to `range` (min, max), since e.g. a big enough board would take distances past 1.0.  With a
+    /// `sensing_range`, wall and snake distances are capped at that many cells, so everything
+    /// beyond it reads alike as "far" and the net attends to what's nearby.
+    pub fn encode(&self, game: &SnakeGame, transform: BoardTransform, range: (f32, f32), sensing_range: Option<i16>) -> Vec<f32> {
+        let (mut wall_dist, mut snake_dist) = game.wall_and_body_distances();
+        if let Some(cells) = sensing_range {
+            for dist in wall_dist.iter_mut().chain(snake_dist.iter_mut()) { *dist = (*dist).min(cells); }
+        }
+        // The net's direction i is board_dirs[i] on the real board
+        let board_dirs = DIRECTIONS.map(|dir| transform.to_board_direction(dir));
+        let apple_offset = transform.apply_to_offset(game.snake.head_location - game.apple.location);
+        let snake_length = game.snake.length();
+
+        let mut inputs = Vec::with_capacity(20);
+        inputs.extend(board_dirs.map(|dir| wall_dist[dir.to_index()] as f32 / 40.0));
+        inputs.extend(board_dirs.map(|dir| snake_dist[dir.to_index()] as f32 / 40.0));
+        inputs.extend([
+            apple_offset.x as f32 / 35.0,   // Max distance = RMS(30,40) = 35.36
+            apple_offset.y as f32 / 35.0,
+            snake_length as f32 / 1200.0,
+            1.0,
+        ]);
+        if matches!(self, InputEncoder::WithDangers | InputEncoder::WithTail) {
+            inputs.extend(board_dirs.map(|dir| {
+                let kind = game.grid.get_cell(game.snake.head_location + dir.to_point()).kind;
+                if kind == CellKind::Wall || kind == CellKind::Snake { 1.0 } else { 0.0 }
+            }));
+        }
+        if *self == InputEncoder::WithTail {
+            let vacating_tail = game.snake.locations.back().copied().filter(|_| game.snake.to_grow == 0);
+            inputs.extend(board_dirs.map(|dir| {
+                if Some(game.snake.head_location + dir.to_point()) == vacating_tail { 1.0 } else { 0.0 }
+            }));
+        }
+        inputs.iter_mut().for_each(|input| *input = input.clamp(range.0, range.1));
+        inputs
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snake_game::{GridPoint, PlaybackEvents};
+
+    #[test]
+    fn test_feature_names_match_inputs() {
+        let game = SnakeGame::new(None);
+        for encoder in InputEncoder::ALL {
+            for transform in [BoardTransform::Identity, BoardTransform::MirrorX, BoardTransform::MirrorY] {
+                assert_eq!(encoder.input_count(), encoder.encode(&game, transform, InputEncoder::DEFAULT_RANGE, None).len(), "{encoder:?}");
+            }
+        }
+        assert_eq!(12, InputEncoder::Standard.input_count());
+    }
+
+    #[test]
+    fn test_tail_flag_only_when_tail_vacates() {
+        // A 2x2 loop, with the tail just east of the head
+        let mut game = SnakeGame::new(None);
+        game.grid.restart();
+        let locations = [GridPoint::new(5, 5), GridPoint::new(5, 6), GridPoint::new(6, 6), GridPoint::new(6, 5)];
+        game.snake.locations.clear();
+        for pt in locations {
+            game.grid.get_cell_mut(pt).kind = CellKind::Snake;
+            game.snake.locations.push_back(pt);
+        }
+        game.snake.head_location = locations[0];
+        let tail_flags = |game: &SnakeGame| InputEncoder::WithTail.encode(game, BoardTransform::Identity, InputEncoder::DEFAULT_RANGE, None)[16..].to_vec();
+
+        game.snake.to_grow = 0;
+        assert_eq!(vec![0.0, 1.0, 0.0, 0.0], tail_flags(&game));
+        game.snake.to_grow = 3;
+        assert_eq!(vec![0.0, 0.0, 0.0, 0.0], tail_flags(&game));
+    }
+
+    #[test]
+    fn test_distances_capped_at_sensing_range() {
+        // Head at (5, 5) heading east: the west wall is 4 cells away, the east wall 33
+        let game = SnakeGame::from_playback(&[
+            PlaybackEvents::NewGame,
+            PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+            PlaybackEvents::NewAppleLocation(GridPoint::new(20, 20)),
+        ]);
+        let walls = |sensing_range| InputEncoder::Standard.encode(&game, BoardTransform::Identity, InputEncoder::DEFAULT_RANGE, sensing_range)[..4].to_vec();
+        let (wall_dist, _) = game.wall_and_body_distances();
+        assert_eq!(33, wall_dist[Direction::East.to_index()]);
+        assert_eq!(33.0 / 40.0, walls(None)[1]);
+        let capped = walls(Some(10));
+        assert_eq!(10.0 / 40.0, capped[1]);                     // East, beyond the range
+        assert_eq!(walls(None)[3], capped[3]);                  // West, within it
+        assert!(capped.iter().all(|&wall| wall <= 10.0 / 40.0), "{capped:?}");
+    }
+
+    #[test]
+    fn test_inputs_clamped_to_range() {
+        let game = SnakeGame::new(None);
+        let inputs = InputEncoder::Standard.encode(&game, BoardTransform::Identity, (-0.1, 0.1), None);
+        assert!(inputs.iter().all(|&input| (-0.1..=0.1).contains(&input)), "{inputs:?}");
+        // The bias input, 1.0, lands exactly on the boundary
+        assert_eq!(0.1, inputs[11]);
+        let unclamped = InputEncoder::Standard.encode(&game, BoardTransform::Identity, (f32::MIN, f32::MAX), None);
+        assert_eq!(1.0, unclamped[11]);
+    }
+}
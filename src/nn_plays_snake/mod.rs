@@ -1,15 +1,28 @@
 use core::fmt;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use rand::{thread_rng, Rng};
+use log::error;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
+use crate::neural_net::id_registry::IdRegistry;
+use crate::sim_logger::{self, sim_debug, sim_info};
 use crate::neural_net::nets::{Net, NetParams};
-use crate::neural_net::populations::{FitnessInfo, PopulationParams};
-use crate::snake_game::{Direction, GameState, SnakeGame};
+use crate::neural_net::populations::{FitnessInfo, PopulationParams, ELITE_COUNT};
+use crate::snake_game::{Direction, GameConfig, GameState, GridPoint, Playback, PlaybackEvents, SnakeGame};
 use crate::neural_net::{populations::Population, nets::MutationParams};
 
+mod input_encoder;
+pub use input_encoder::InputEncoder;
+mod novelty;
+pub use novelty::NoveltyArchive;
+mod snapshot;
+
 // TODO list:
 // x Support save of Nets
 // - Support load of Nets
@@ -32,10 +45,10 @@ use crate::neural_net::{populations::Population, nets::MutationParams};
 //      - Stash top 5% or so, and reboot population
 //      !!! CONSIDER: Using NEAT approach to retaining genetically distinct Nets in population?
 //      - CONSIDER: Using different fitness functions to create diversity, e.g.:
-//          - Instead of 75% max + 25% ave, use
-//              - only max
-//              - only ave
-//              - only min
+//          x Instead of 75% max + 25% ave, use (see FitnessAggregation)
+//              x only max
+//              x only ave
+//              x only min
 //          - Add severe penalty for Hidden node count or moves or moves beyond unique ones
 //          - Vary mutations rate: multiplier of 1.0, 2.0, 5.0, 0.2 for a while (100 generations?)
 //          - Vary population size
@@ -54,20 +67,26 @@ use crate::neural_net::{populations::Population, nets::MutationParams};
 
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct MyFitnessInfo {
-    fitness: f32,
-    apples:  f32,
-    visited: f32,
-    moves:   f32,
+    fitness:  f32,
+    apples:   f32,
+    visited:  f32,
+    moves:    f32,
+    #[serde(default)]
+    coverage: f32,      // Defaulted, as nets stashed before it was added lack it
+    #[serde(default)]
+    detour:   f32,      // Moves taken to reach apples beyond the shortest (manhattan) distance
     //net_id: Option<NetId>,
 }
 
 impl Default for MyFitnessInfo {
     fn default() -> Self {
         MyFitnessInfo {
-            fitness: f32::MIN,
-            apples:  0.0,
-            visited: 0.0,
-            moves:   0.0,
+            fitness:  f32::MIN,
+            apples:   0.0,
+            visited:  0.0,
+            moves:    0.0,
+            coverage: 0.0,
+            detour:   0.0,
             //net_id: None,
         }
     }
@@ -80,13 +99,13 @@ impl FitnessInfo for MyFitnessInfo {
 
 impl fmt::Display for MyFitnessInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:.1} (apples:{:.1}, visited:{:.1}, moves={:.1})", self.fitness, self.apples, self.visited, self.moves)
+        write!(f, "{:.1} (apples:{:.1}, visited:{:.1}, moves={:.1}, coverage={:.3}, detour={:.1})", self.fitness, self.apples, self.visited, self.moves, self.coverage, self.detour)
     }
 }
 
 impl fmt::Debug for MyFitnessInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:.1} (apples:{:.1}, visited:{:.1}, moves={:.1})", self.fitness, self.apples, self.visited, self.moves)
+        write!(f, "{:.1} (apples:{:.1}, visited:{:.1}, moves={:.1}, coverage={:.3}, detour={:.1})", self.fitness, self.apples, self.visited, self.moves, self.coverage, self.detour)
     }
 }
 impl std::ops::Mul<f32> for MyFitnessInfo {
@@ -94,10 +113,12 @@ impl std::ops::Mul<f32> for MyFitnessInfo {
 
     fn mul(self, rhs: f32) -> Self::Output {
         Self::Output {
-            fitness: rhs * self.fitness,
-            visited: rhs * self.visited,
-            apples:  rhs * self.apples,
-            moves:   rhs * self.moves,
+            fitness:  rhs * self.fitness,
+            visited:  rhs * self.visited,
+            apples:   rhs * self.apples,
+            moves:    rhs * self.moves,
+            coverage: rhs * self.coverage,
+            detour:   rhs * self.detour,
         }
     }
 }
@@ -107,29 +128,34 @@ impl std::ops::Add for MyFitnessInfo {
 
     fn add(self, rhs: Self) -> Self::Output {
         Self::Output {
-            fitness: self.fitness + rhs.fitness,
-            visited: self.visited + rhs.visited,
-            apples:  self.apples  + rhs.apples,
-            moves:   self.moves   + rhs.moves,
+            fitness:  self.fitness  + rhs.fitness,
+            visited:  self.visited  + rhs.visited,
+            apples:   self.apples   + rhs.apples,
+            moves:    self.moves    + rhs.moves,
+            coverage: self.coverage + rhs.coverage,
+            detour:   self.detour   + rhs.detour,
         }
     }
 }
 
 impl std::ops::AddAssign<&Self> for MyFitnessInfo {
     fn add_assign(&mut self, rhs: &Self) {
-        self.fitness += rhs.fitness;
-        self.visited += rhs.visited;
-        self.apples  += rhs.apples;
-        self.moves   += rhs.moves;
+        self.fitness  += rhs.fitness;
+        self.visited  += rhs.visited;
+        self.apples   += rhs.apples;
+        self.moves    += rhs.moves;
+        self.coverage += rhs.coverage;
+        self.detour   += rhs.detour;
     }
 }
 
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum EraFitness {
     Normal = 0,
     FavorVisits,
     FavorMoves,
+    Novelty,        // Only with `MyMetaParams::novelty_neighbors`
 }
 
 
@@ -143,46 +169,521 @@ pub struct EraInfo {
 }
 
 
-#[allow(clippy::identity_op)]
-pub const NUM_INPUTS: usize = 
-    4 /*NSEW dist to wall*/ +
-    4 /*NSEW dist to snake*/ +
-    2 /*x,y head - x,y apple*/ +
-    1 /*snake length*/ +
-    1 /*1.0 (bias)*/ +
-    0;
-pub const INPUT_NAMES: [&str; NUM_INPUTS] = [
-    "WallN", "WallE", "WallS", "WallW",
-    "SnakeN", "SnakeE", "SnakeS", "SnakeW",
-    "AppleDistX", "AppleDistY",
-    "SnakeLen",
-    "1.0",
-];
 pub const NUM_OUTPUTS: usize = 4;
 pub const OUTPUT_NAMES: [&str; NUM_OUTPUTS] = [
     "MoveN", "MoveE", "MoveS", "MoveW",
 ];
 
 
+/// How the Net's outputs are turned into a move.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OutputPolicy {
+    /// Always take the move with the largest output.
+    Argmax,
+    /// Sample a move from softmax(outputs / temperature).  Lower temperature is greedier (and
+    /// approaches Argmax as temperature -> 0); higher temperature approaches uniformly random.
+    SoftmaxSample { temperature: f32 },
+}
+
+/// How the Net sees the board.  Playing the same game under several transforms rewards strategies
+/// that don't depend on the board's orientation.  The game itself is always played on the real
+/// board; the inputs are transformed on the way into the net, and its move on the way out.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BoardTransform {
+    Identity,
+    /// Mirrored left-to-right, so East and West trade places.
+    MirrorX,
+    /// Mirrored top-to-bottom, so North and South trade places.
+    MirrorY,
+}
+
+impl BoardTransform {
+    fn apply_to_offset(self, offset: GridPoint) -> GridPoint {
+        match self {
+            BoardTransform::Identity => offset,
+            BoardTransform::MirrorX  => GridPoint::new(-offset.x,  offset.y),
+            BoardTransform::MirrorY  => GridPoint::new( offset.x, -offset.y),
+        }
+    }
+
+    /// Maps a direction on the transformed board (i.e. as chosen by the net) back to the real board.
+    pub fn to_board_direction(self, dir: Direction) -> Direction {
+        match (self, dir) {
+            (BoardTransform::MirrorX, Direction::East)  => Direction::West,
+            (BoardTransform::MirrorX, Direction::West)  => Direction::East,
+            (BoardTransform::MirrorY, Direction::North) => Direction::South,
+            (BoardTransform::MirrorY, Direction::South) => Direction::North,
+            _ => dir,
+        }
+    }
+}
+
+/// How many games each Net plays to determine its fitness, as a function of generation.  Fewer
+/// games is faster but noisier; later generations benefit from more games to rank fine differences.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GamesPerNetSchedule {
+    Fixed(usize),
+    /// Ramps linearly from `start` games at generation 0 to `end` games at generation `generations`,
+    /// then stays at `end`.
+    Linear { start: usize, end: usize, generations: usize },
+}
+
+impl GamesPerNetSchedule {
+    pub fn games_at(self, generation: usize) -> usize {
+        match self {
+            Self::Fixed(games) => games,
+            Self::Linear { start, end, generations } => {
+                if generation >= generations { return end; }
+                let frac = generation as f64 / generations as f64;
+                let games = start as f64 + (end as f64 - start as f64) * frac;
+                (games.round() as usize).max(1)
+            }
+        }
+    }
+}
+
+
+/// How a Net's per-game fitnesses combine into its fitness.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FitnessAggregation {
+    /// Its best game.
+    Max,
+    Mean,
+    /// Its worst game, which rewards consistency.
+    Min,
+    /// `w * Max + (1 - w) * Mean`.
+    BlendMaxMean { w: f32 },
+}
+
+impl FitnessAggregation {
+    /// Combines the (non-empty) `games`' fitness infos.  Max and Min take the whole info of that game.
+    pub fn aggregate(self, games: &[MyFitnessInfo]) -> MyFitnessInfo {
+        let by_fitness = |a: &&MyFitnessInfo, b: &&MyFitnessInfo| a.fitness.total_cmp(&b.fitness);
+        let max  = || *games.iter().max_by(by_fitness).unwrap();
+        let mean = || {
+            let mut sum = MyFitnessInfo { fitness: 0.0, ..Default::default() };
+            games.iter().for_each(|game| sum += game);
+            sum * (1.0 / games.len() as f32)
+        };
+        match self {
+            Self::Max  => max(),
+            Self::Mean => mean(),
+            Self::Min  => *games.iter().min_by(by_fitness).unwrap(),
+            Self::BlendMaxMean { w } => max() * w + mean() * (1.0 - w),
+        }
+    }
+}
+
+/// Fitness taken away from a net that keeps its head boxed into a small region, e.g. by coiling
+/// up somewhere safe to rack up moves.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TrapPenalty {
+    /// The head is "trapped" while `SnakeGame::reachable_free_space()` is below this.
+    pub min_free_space: usize,
+    /// Consecutive trapped moves that go unpunished, e.g. while squeezing past its own body.
+    pub grace_moves: usize,
+    /// Fitness lost for each trapped move beyond `grace_moves` in a row.
+    pub per_move: f32,
+}
+
+
 #[derive(Clone,Debug)]
 pub struct MyMetaParams {
     pub max_generations: usize, // 100_000
-    pub games_per_net: usize, // 10
+    pub games_per_net: GamesPerNetSchedule, // Fixed(2)
+    pub fitness_aggregation: FitnessAggregation, // BlendMaxMean { w: 0.75 }
     pub generations_between_events: usize, // 25
+    pub output_policy: OutputPolicy,
+    pub min_population_after_cataclysm: usize, // 1_000
+    pub board_transforms: Vec<BoardTransform>, // [Identity]; add e.g. MirrorX to also score mirrored play
+    pub apple_value_decay: Option<f32>, // None; else each apple is worth decay^(moves taken to reach it)
+    pub starvation_limit: Option<usize>, // None; else games end after this many moves without eating
+    pub initial_to_grow: usize,         // 0; else each game starts with the snake due to grow this much, as if mid-game
+    pub trap_penalty: Option<TrapPenalty>, // None; else fitness is lost for staying boxed into a small region
+    pub input_encoder: InputEncoder,    // Must match meta.net_params' inputs
+    pub input_range: (f32, f32),        // (min, max) that every input is clamped to; InputEncoder::DEFAULT_RANGE
+    pub sensing_range: Option<i16>,     // None; else wall and snake distances beyond this many cells all read as this many
+    pub input_dropout: Option<f64>,     // None; else while training, each input is zeroed with this probability on every move
+    pub resurrection_jitter: Option<f32>, // None; else resurrected champions get Gaussian weight noise of this sigma
+    pub length_weights: [f32; 4],       // Per EraFitness, in order; fitness += weight * snake length at game end
+    pub efficiency_weights: [f32; 4],   // Per EraFitness, in order; fitness += weight * points_visited / moves, which is 1.0 for never revisiting
+    pub novelty_neighbors: Option<usize>, // None; else Novelty eras join the rotation, scoring against this many nearest behaviors
+    pub apple_histogram_every: Option<usize>, // None; else log how many nets ate each apple count every N generations
+    pub bail_early: bool,               // true; false plays each game out (up to MAX_MOVES_PER_GAME), e.g. to judge a champion
+    pub seeded_boards: bool,            // false; true plays every net of a generation on the same boards, seeded by the generation
+    pub era_mutation_params: [Option<MutationParams>; 4], // Per EraFitness, in order; None breeds with meta.mutation_params
     pub meta: PopulationParams,
 }
 
+/// Why a `MyMetaParams` can't be trained with.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfigError(pub String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid config: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl MyMetaParams {
+    /// Catches settings that would otherwise panic, or silently do nothing, partway through training.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let fail = |msg: String| Err(ConfigError(msg));
+
+        if self.max_generations == 0 { return fail("max_generations must be nonzero".into()); }
+        match self.games_per_net {
+            GamesPerNetSchedule::Fixed(0) => return fail("games_per_net must be nonzero".into()),
+            GamesPerNetSchedule::Linear { start, end, .. } if start == 0 || end == 0 =>
+                return fail(format!("games_per_net must be nonzero, not Linear from {start} to {end}")),
+            _ => (),
+        }
+        if let FitnessAggregation::BlendMaxMean { w } = self.fitness_aggregation {
+            if !(0.0..=1.0).contains(&w) { return fail(format!("BlendMaxMean's w must be in [0, 1], not {w}")); }
+        }
+        if self.generations_between_events == 0 { return fail("generations_between_events must be nonzero".into()); }
+        if let OutputPolicy::SoftmaxSample { temperature } = self.output_policy {
+            if temperature.is_nan() || temperature < 0.0 { return fail(format!("softmax temperature must be >= 0, not {temperature}")); }
+        }
+        if self.board_transforms.is_empty() { return fail("board_transforms must not be empty".into()); }
+        if let Some(decay) = self.apple_value_decay {
+            if !(0.0..=1.0).contains(&decay) || decay == 0.0 { return fail(format!("apple_value_decay must be in (0, 1], not {decay}")); }
+        }
+        if self.starvation_limit == Some(0) { return fail("starvation_limit must be nonzero".into()); }
+        if let Some(TrapPenalty { per_move, .. }) = self.trap_penalty {
+            if !per_move.is_finite() || per_move < 0.0 { return fail(format!("trap_penalty's per_move must be finite and >= 0, not {per_move}")); }
+        }
+        if self.novelty_neighbors == Some(0) { return fail("novelty_neighbors must be nonzero".into()); }
+        if self.apple_histogram_every == Some(0) { return fail("apple_histogram_every must be nonzero".into()); }
+
+        let meta = &self.meta;
+        // Each generation carries its ELITE_COUNT fittest nets forward unchanged
+        if meta.population_size < ELITE_COUNT { return fail(format!("population_size must be at least {ELITE_COUNT}, not {}", meta.population_size)); }
+        if !(0.0..=1.0).contains(&meta.cloned_fraction) { return fail(format!("cloned_fraction must be in [0, 1], not {}", meta.cloned_fraction)); }
+        if let Some(sharing) = meta.fitness_sharing {
+            if sharing.threshold.is_nan() || sharing.threshold <= 0.0 { return fail(format!("fitness_sharing.threshold must be > 0, not {}", sharing.threshold)); }
+            if sharing.sample == 0 { return fail("fitness_sharing.sample must be nonzero".into()); }
+        }
+        if meta.mutated_elite_count > ELITE_COUNT {
+            return fail(format!("mutated_elite_count must be at most {ELITE_COUNT}, not {}", meta.mutated_elite_count));
+        }
+        if self.min_population_after_cataclysm > meta.population_size {
+            return fail(format!("min_population_after_cataclysm ({}) must not exceed population_size ({})", self.min_population_after_cataclysm, meta.population_size));
+        }
+        if let Some(rate) = self.input_dropout {
+            if !(0.0..1.0).contains(&rate) { return fail(format!("input_dropout must be in [0, 1), not {rate}")); }
+        }
+        if let Some(sigma) = self.resurrection_jitter {
+            if sigma.is_nan() || sigma < 0.0 { return fail(format!("resurrection_jitter must be >= 0, not {sigma}")); }
+        }
+        if let Some(cells) = self.sensing_range {
+            if cells <= 0 { return fail(format!("sensing_range must be at least 1 cell, not {cells}")); }
+        }
+        let (input_min, input_max) = self.input_range;
+        if input_min.is_nan() || input_max.is_nan() || input_min >= input_max {
+            return fail(format!("input_range's min must be less than its max, not {:?}", self.input_range));
+        }
+        if meta.net_params.input_count != self.input_encoder.input_count() {
+            return fail(format!("net_params.input_count ({}) doesn't match {:?}'s {} inputs", meta.net_params.input_count, self.input_encoder, self.input_encoder.input_count()));
+        }
+        if meta.net_params.output_count != NUM_OUTPUTS {
+            return fail(format!("net_params.output_count must be {NUM_OUTPUTS}, not {}", meta.net_params.output_count));
+        }
+
+        Self::validate_mutation_params(&meta.mutation_params)?;
+        for (i, mp) in self.era_mutation_params.iter().enumerate() {
+            if let Some(mp) = mp {
+                Self::validate_mutation_params(mp).map_err(|e| ConfigError(format!("era_mutation_params[{i}]: {}", e.0)))?;
+            }
+        }
+
+        if ERA_FIRST_PORTION_SIZE >= ERA_SIZE { return fail("ERA_FIRST_PORTION_SIZE must be less than ERA_SIZE".into()); }
+        Ok(())
+    }
+
+    fn validate_mutation_params(mp: &MutationParams) -> Result<(), ConfigError> {
+        let fail = |msg: String| Err(ConfigError(msg));
+        let check_prob = |name: &str, p: f64| if (0.0..=1.0).contains(&p) { Ok(()) } else { fail(format!("{name} must be in [0, 1], not {p}")) };
+
+        check_prob("prob_mutate_activation_function_of_node", mp.prob_mutate_activation_function_of_node)?;
+        check_prob("prob_mutate_weight", mp.prob_mutate_weight)?;
+        check_prob("prob_reset_weight_when_mutating", mp.prob_reset_weight_when_mutating)?;
+        check_prob("prob_toggle_enabled", mp.prob_toggle_enabled)?;
+        check_prob("prob_remove_connection", mp.prob_remove_connection)?;
+        check_prob("prob_add_connection", mp.prob_add_connection)?;
+        check_prob("prob_remove_node", mp.prob_remove_node)?;
+        check_prob("prob_add_node", mp.prob_add_node)?;
+        if mp.structural_mutations_per_call == 0 {
+            return fail("structural_mutations_per_call must be at least 1".into());
+        }
+        if !(0.0..1.0).contains(&mp.max_weight_change_frac) {
+            return fail(format!("max_weight_change_frac must be in [0, 1), not {}", mp.max_weight_change_frac));
+        }
+        if mp.activation_function_weights.iter().any(|&(_, w)| w.is_nan() || w < 0.0) {
+            return fail("activation_function_weights must not be negative".into());
+        }
+        if !mp.activation_function_weights.is_empty() && mp.activation_function_weights.iter().all(|&(_, w)| w == 0.0) {
+            return fail("activation_function_weights needs at least one positive weight (or leave it empty)".into());
+        }
+        Ok(())
+    }
+}
+
+/// Accumulates, over a game, how many more moves the snake took to reach each apple than the
+/// manhattan distance from where its head was when that apple appeared.
+struct DetourTracker {
+    optimal_moves: usize,
+    moves: usize,
+    detour: usize,
+    apple_moves: Vec<usize>,    // Moves taken to reach each apple since the previous one
+}
+
+impl DetourTracker {
+    fn new(game: &SnakeGame) -> Self {
+        Self { optimal_moves: Self::manhattan_to_apple(game), moves: 0, detour: 0, apple_moves: Vec::new() }
+    }
+
+    /// How many apples the game's apples are worth, with each one decayed by the moves taken to reach it.
+    fn apple_value(&self, apple_value_decay: Option<f32>) -> f32 {
+        match apple_value_decay {
+            None        => self.apple_moves.len() as f32,
+            Some(decay) => self.apple_moves.iter().map(|&moves| decay.powi(moves as i32)).sum(),
+        }
+    }
+
+    fn manhattan_to_apple(game: &SnakeGame) -> usize {
+        let offset = game.apple.location - game.snake.locations[0];
+        (offset.x.unsigned_abs() + offset.y.unsigned_abs()) as usize
+    }
+
+    /// Call after every move, saying whether that move ate the apple.
+    fn after_move(&mut self, game: &SnakeGame, has_eaten: bool) {
+        self.moves += 1;
+        if has_eaten {
+            self.detour += self.moves.saturating_sub(self.optimal_moves);
+            self.apple_moves.push(self.moves);
+            self.optimal_moves = Self::manhattan_to_apple(game);
+            self.moves = 0;
+        }
+    }
+}
+
+/// Counts, over a game, the moves the snake spent trapped beyond `TrapPenalty::grace_moves` in a row.
+struct TrapTracker {
+    trap_penalty: Option<TrapPenalty>,
+    trapped_in_a_row: usize,
+    penalized_moves: usize,
+}
+
+impl TrapTracker {
+    fn new(trap_penalty: Option<TrapPenalty>) -> Self {
+        Self { trap_penalty, trapped_in_a_row: 0, penalized_moves: 0 }
+    }
+
+    /// Call after every move.  Does nothing (not even the flood fill) without a `TrapPenalty`.
+    fn after_move(&mut self, game: &SnakeGame) {
+        let Some(trap_penalty) = self.trap_penalty else { return; };
+        if game.reachable_free_space() < trap_penalty.min_free_space {
+            self.trapped_in_a_row += 1;
+            if self.trapped_in_a_row > trap_penalty.grace_moves { self.penalized_moves += 1; }
+        } else {
+            self.trapped_in_a_row = 0;
+        }
+    }
+
+    fn penalty(&self) -> f32 {
+        self.trap_penalty.map_or(0.0, |trap_penalty| trap_penalty.per_move * self.penalized_moves as f32)
+    }
+}
+
+/// One generation's summary, as written (one JSON object per line) by `set_metrics_jsonl()`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GenerationStats {
+    pub generation:      usize,
+    pub best:            MyFitnessInfo,
+    pub mean:            MyFitnessInfo,
+    pub fitness_kind:    EraFitness,
+    pub eras:            usize,
+    pub is_era_boundary: bool,
+    pub events:          Vec<String>,   // Cataclysms and resurrections fired just before this generation
+}
+
+impl GenerationStats {
+    /// Summarizes `nets`, which must all have been scored.
+    fn new(generation: usize, era_info: &EraInfo, events: &[&str], nets: &[Net<MyFitnessInfo>]) -> Self {
+        let best = nets.iter().map(|n| n.fitness_info).max_by(|a, b| a.fitness.total_cmp(&b.fitness)).unwrap_or_default();
+        let mut sum = MyFitnessInfo { fitness: 0.0, ..Default::default() };
+        for net in nets { sum += &net.fitness_info; }
+        Self {
+            generation,
+            best,
+            mean: sum * (1.0 / nets.len().max(1) as f32),
+            fitness_kind: era_info.fitness_kind,
+            eras: era_info.eras,
+            is_era_boundary: era_info.is_era_boundary,
+            events: events.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
 pub struct StashInfo {
     pub net: Net<MyFitnessInfo>,
     pub generation: usize,
 }
 
+/// A champion together with the games it was scored on, saved as one file so they can't get separated.
+#[derive(Serialize, Deserialize)]
+pub struct ChampionRecord {
+    pub net:          Net<MyFitnessInfo>,
+    pub playback:     Playback,     // Its last game, which earned its whole fitness if it played only one
+    #[serde(default)]
+    pub earlier_games: Vec<Playback>,   // The rest of the games its fitness was aggregated from, in order; older records have none
+    pub generation:   usize,
+    pub fitness_info: MyFitnessInfo,
+}
+
+impl ChampionRecord {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        serde_json::from_str(&s).map_err(|e| format!("parsing {path}: {e}"))
+    }
+
+    /// Scores the recorded games again, and aggregates them as `play_games()` would have (so
+    /// `my_meta` needs the `board_transforms` and `fitness_aggregation` it was trained with).
+    pub fn replay_fitness_info(&self, era_info: &EraInfo, my_meta: &MyMetaParams) -> MyFitnessInfo {
+        let play_fitness_infos = self.earlier_games.iter().chain([&self.playback])
+            .map(|playback| Self::replay_game(playback, era_info, my_meta))
+            .collect::<Vec<_>>();
+        NnPlaysSnake::aggregate_plays(&play_fitness_infos, my_meta)
+    }
+
+    /// Scores `playback`'s game again, as `run_one_game()` would have.
+    fn replay_game(playback: &Playback, era_info: &EraInfo, my_meta: &MyMetaParams) -> MyFitnessInfo {
+        let mut game = SnakeGame::from_playback_with_config(&playback.config, playback.prefix_through_move(0));
+        game.starvation_limit = my_meta.starvation_limit;
+        let mut moves = recorded_moves(playback);
+        NnPlaysSnake::play_and_score(&mut game, era_info, my_meta, |_| moves.next())
+    }
+}
+
+/// Each move of `playback`'s game, with where the apple it ate (if any) went next.
+fn recorded_moves(playback: &Playback) -> impl Iterator<Item = (Direction, Option<GridPoint>)> + '_ {
+    let events = &playback.playback_events;
+    events.iter().enumerate().filter_map(|(i, event)| match event {
+        PlaybackEvents::MoveSnake(dir) => match events.get(i + 1) {
+            Some(&PlaybackEvents::NewAppleLocation(pt)) => Some((*dir, Some(pt))),
+            _ => Some((*dir, None)),
+        },
+        _ => None,
+    })
+}
+
+/// What a net saw and said on one move of a traced game (see `NnPlaysSnake::trace_playback()`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    pub inputs:    Vec<f32>,
+    pub outputs:   Vec<f32>,
+    pub direction: Direction,   // The move made, on the board (i.e. after any BoardTransform)
+}
+
+/// A run's population and progress, written when it's stopped early (e.g. by Ctrl-C).
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub generation:  usize,     // The next generation to run
+    pub max_info:    MyFitnessInfo,
+    pub id_registry: IdRegistry,
+    pub nets:        Vec<Net<MyFitnessInfo>>,
+}
+
+impl Checkpoint {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        serde_json::from_str(&s).map_err(|e| format!("parsing {path}: {e}"))
+    }
+}
+
+/// Called with the net, its fitness, the playbacks of its earlier and of its last game, and the
+/// generation whenever a new champion is found.
+pub type ChampionCallback = Box<dyn FnMut(&Net<MyFitnessInfo>, &MyFitnessInfo, &[Playback], &Playback, usize)>;
+
+/// What happened over a `run_x_generations()` run.
+#[derive(Clone, Debug, Default)]
+pub struct RunSummary {
+    pub generations:   usize,
+    pub champions:     usize,   // New champions found (and stashed)
+    pub best:          MyFitnessInfo,
+    pub eras:          usize,   // Era boundaries crossed
+    pub cataclysms:    usize,
+    pub resurrections: usize,
+    pub checkpoint:    Option<PathBuf>, // Where the run's state was saved, if it was stopped early
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Generations:   {}", self.generations)?;
+        writeln!(f, "Champions:     {}", self.champions)?;
+        writeln!(f, "Best fitness:  {:.1}", self.best.fitness)?;
+        writeln!(f, "Best apples:   {:.1}", self.best.apples)?;
+        writeln!(f, "Eras:          {}", self.eras)?;
+        writeln!(f, "Cataclysms:    {}", self.cataclysms)?;
+        writeln!(f, "Resurrections: {}", self.resurrections)?;
+        if let Some(path) = &self.checkpoint {
+            writeln!(f, "Stopped early; checkpoint: {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// How a Net fared across many boards; see `NnPlaysSnake::evaluate_robustness()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RobustnessReport {
+    pub games:        usize,
+    pub mean_apples:  f32,
+    pub std_apples:   f32,
+    pub min_apples:   usize,
+    pub max_apples:   usize,
+    pub died:         f32,  // Fraction of games that ended by crashing (or starving)
+    pub move_limited: f32,  // Fraction of games stopped while still going, e.g. at MAX_MOVES_PER_GAME
+}
+
+impl RobustnessReport {
+    fn new(apples: &[usize], died: usize, move_limited: usize) -> Self {
+        if apples.is_empty() { return Self::default(); }
+        let games = apples.len();
+        let mean_apples = apples.iter().sum::<usize>() as f32 / games as f32;
+        let variance = apples.iter().map(|&a| (a as f32 - mean_apples).powi(2)).sum::<f32>() / games as f32;
+        Self {
+            games,
+            mean_apples,
+            std_apples:   variance.sqrt(),
+            min_apples:   *apples.iter().min().unwrap(),
+            max_apples:   *apples.iter().max().unwrap(),
+            died:         died as f32 / games as f32,
+            move_limited: move_limited as f32 / games as f32,
+        }
+    }
+}
+
 pub struct NnPlaysSnake {
     game: SnakeGame,
     my_meta: MyMetaParams,
     population: Population<MyFitnessInfo>,
     max_info: MyFitnessInfo,
     stashed_nets: Vec<StashInfo>,
+    on_new_champion: Option<ChampionCallback>,
+    dump_fitness_dir: Option<PathBuf>,
+    metrics_jsonl: Option<BufWriter<File>>, // Where each generation's GenerationStats is appended
+    events_fired: Vec<&'static str>,        // By pick_and_apply_event() before the current generation
+    summary: RunSummary,    // Tallies for the current (or last) run
+    next_generation: usize, // Where run_x_generations() starts: where the last run (or a resumed Checkpoint) left off
+    novelty_archive: NoveltyArchive,
+    stop_requested: Arc<AtomicBool>,    // Checked before each generation; see `stop_requested()`
+    checkpoint_dir: PathBuf,
+    champion_snapshots: bool,   // Also write a PNG of each new champion's final board
+    champion_traces: bool,      // Also write each new champion's per-move inputs and outputs
+    #[cfg(feature = "progress")]
+    show_progress: bool,
 }
 
 
@@ -191,33 +692,58 @@ impl Default for NnPlaysSnake {
 }
 
 pub const ERA_SIZE: usize = 200;
+/// Even without `MyMetaParams::bail_early`, no game runs longer than this, so a looping net still ends.
+pub const MAX_MOVES_PER_GAME: usize = 100_000;
 pub const ERA_FIRST_PORTION_SIZE: usize = 100;
+/// Fitness per unit of `NoveltyArchive::novelty()` in Novelty eras.
+pub const NOVELTY_SCALE: f32 = 1_000.0;
+/// Moves (at most 21, at 3 bits each in a u64) that make up a `behavior_fingerprint()`.
+pub const FINGERPRINT_MOVES: usize = 20;
+/// The `SnakeGame::from_seed()` board every net's `behavior_fingerprint()` is taken on.
+const FINGERPRINT_SEED: u64 = 0;
 
 impl NnPlaysSnake {
     pub fn new() -> Self {
+        let input_encoder = InputEncoder::Standard;
         let my_meta = MyMetaParams {
             max_generations: 100_000,
-            games_per_net: 2,
+            games_per_net: GamesPerNetSchedule::Fixed(2),
+            fitness_aggregation: FitnessAggregation::BlendMaxMean { w: 0.75 },
             generations_between_events: 25,
+            output_policy: OutputPolicy::Argmax,
+            min_population_after_cataclysm: 1_000,
+            board_transforms: vec![BoardTransform::Identity],
+            apple_value_decay: None,
+            starvation_limit: None,
+            initial_to_grow: 0,
+            trap_penalty: None,
+            input_encoder,
+            input_range: InputEncoder::DEFAULT_RANGE,
+            sensing_range: None,
+            input_dropout: None,
+            resurrection_jitter: None,
+            length_weights: [0.0; 4],
+            efficiency_weights: [0.0; 4],
+            novelty_neighbors: None,
+            apple_histogram_every: None,
+            bail_early: true,
+            seeded_boards: false,
+            era_mutation_params: [None, None, None, None],
             meta: PopulationParams {
                 population_size: 10_000,
                 net_params: NetParams {
-                    input_count: NUM_INPUTS,
-                    input_names: Some(&INPUT_NAMES),
+                    input_count: input_encoder.input_count(),
+                    // Leaked once, since NetParams only holds &'static names
+                    input_names: Some(Box::leak(input_encoder.feature_names().into_boxed_slice())),
                     output_count: NUM_OUTPUTS,
                     output_names: Some(&OUTPUT_NAMES),
                 },
-                mutation_params: MutationParams {
-                    prob_add_connection: 0.05,
-                    prob_add_node: 0.03,
-                    prob_mutate_activation_function_of_node: 0.02,
-                    prob_mutate_weight: 0.80,
-                    prob_reset_weight_when_mutating: 0.10,
-                    max_weight_change_frac: 0.10,   // +/- 10% of current value
-                    prob_toggle_enabled: 0.025,
-                    prob_remove_connection: 0.0, // 0.01,
-                    prob_remove_node: 0.0, // 0.025,
-                },
+                mutation_params: MutationParams::neat_default(),
+                seed: None,
+                mutated_elite_count: 0,
+                asexual_only: false,
+                cloned_fraction: 0.25,
+                fitness_sharing: None,
             },
         };
         Self {
@@ -226,15 +752,29 @@ impl NnPlaysSnake {
             population: Population::new(my_meta.meta),
             max_info: MyFitnessInfo::default(),
             stashed_nets: Vec::new(),
+            on_new_champion: Some(Box::new(Self::write_champion_files)),
+            dump_fitness_dir: None,
+            metrics_jsonl: None,
+            events_fired: Vec::new(),
+            summary: RunSummary::default(),
+            next_generation: 0,
+            novelty_archive: NoveltyArchive::default(),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            checkpoint_dir: PathBuf::from("stash"),
+            champion_snapshots: false,
+            champion_traces: false,
+            #[cfg(feature = "progress")]
+            show_progress: true,
         }
     }
 
-    fn compute_era_fitness(eras: usize, gens_since_max: usize) -> EraFitness {
+    fn compute_era_fitness(eras: usize, gens_since_max: usize, with_novelty: bool) -> EraFitness {
         if (gens_since_max % ERA_SIZE) >= ERA_FIRST_PORTION_SIZE { return EraFitness::Normal; }
-        match eras % 3 {
+        match eras % if with_novelty { 4 } else { 3 } {
             0 => EraFitness::Normal,
             1 => EraFitness::FavorVisits,
             2 => EraFitness::FavorMoves,
+            3 => EraFitness::Novelty,
             _ => panic!()
         }
     }
@@ -248,153 +788,650 @@ impl NnPlaysSnake {
             eras,
             is_era_boundary: (gens_since_max % ERA_SIZE) == 0,
             is_end_special_fitness: (gens_since_max % ERA_SIZE) == ERA_FIRST_PORTION_SIZE,
-            fitness_kind: Self::compute_era_fitness(eras, gens_since_max),
+            fitness_kind: Self::compute_era_fitness(eras, gens_since_max, self.my_meta.novelty_neighbors.is_some()),
         }
     }
 
-    pub fn run_x_generations(&mut self) {
+    pub fn run_x_generations(&mut self) -> Result<RunSummary, ConfigError> {
+        self.my_meta.validate()?;
+        self.summary = RunSummary::default();
+        let champions_before = self.stashed_nets.len();
+        #[cfg(feature = "progress")]
+        let progress_bar = self.new_progress_bar();
         let mut stash_population_last = 0;
-        for generation in 0..self.my_meta.max_generations {
+        for generation in self.next_generation..self.my_meta.max_generations {
+            if self.stop_requested.load(Ordering::SeqCst) {
+                sim_info!("Stop requested before gen {generation}; writing a checkpoint");
+                let path = self.checkpoint_dir.join(format!("Checkpoint-Gen{generation:06}.json"));
+                match self.write_checkpoint(generation, &path) {
+                    Ok(()) => self.summary.checkpoint = Some(path),
+                    Err(e) => error!("ERROR writing {}: {e}", path.display()),
+                }
+                break;
+            }
             let era_info = self.eras_since_last_max(generation);
+            self.events_fired.clear();
             if era_info.eras > 0 {
                 if era_info.is_era_boundary {
-                    println!("***** NEW ERA ****************************************** {:?}:{}", era_info.fitness_kind, era_info.eras);
+                    sim_info!("***** NEW ERA ****************************************** {:?}:{}", era_info.fitness_kind, era_info.eras);
+                    self.summary.eras += 1;
                     self.pick_and_apply_event(&era_info);
                 } else if era_info.is_end_special_fitness {
-                    println!("----- End Special Fitness ----- {:?}:{}", era_info.fitness_kind, era_info.eras);
+                    sim_info!("----- End Special Fitness ----- {:?}:{}", era_info.fitness_kind, era_info.eras);
+                }
+            }
+            self.run_one_generation(generation, &era_info, self.my_meta.games_per_net.games_at(generation));
+            self.summary.generations += 1;
+            self.next_generation = generation + 1;
+            // Diagnostics only; not worth computing when nobody will see them
+            if sim_logger::is_enabled(log::Level::Debug) {
+                let count_in_stash = self.population.nets.iter().filter(|n| self.stashed_nets.iter().any(|b| n.id == b.net.id)).count();
+                if count_in_stash != stash_population_last || (generation % 10) == 0 {
+                    stash_population_last = count_in_stash;
+                    let behaviors = self.distinct_behavior_count();
+                    let n = &self.population.nets[0];
+                    let diversity = self.population.average_compatibility_distance(200);
+                    sim_debug!("Best for gen {generation}: {}: fitness={}; {count_in_stash} ({:.1}%); diversity={diversity:.3}; behaviors={behaviors}", n.to_compact_string(), n.fitness_info, 100.0 * count_in_stash as f32 / self.stashed_nets.len() as f32);
                 }
             }
-            self.run_one_generation(generation, &era_info, self.my_meta.games_per_net);
-            let count_in_stash = self.population.nets.iter().filter(|n| self.stashed_nets.iter().any(|b| n.id == b.net.id)).count();
-            if count_in_stash != stash_population_last || (generation % 10) == 0 {
-                stash_population_last = count_in_stash;
-                let n = &self.population.nets[0];
-                println!("Best for gen {generation}: {}: fitness={}; {count_in_stash} ({:.1}%)", n.id, n.fitness_info, 100.0 * count_in_stash as f32 / self.stashed_nets.len() as f32);
+            #[cfg(feature = "progress")]
+            if let Some(progress_bar) = &progress_bar {
+                progress_bar.set_position(generation as u64 + 1);
+                progress_bar.set_message(format!("best fitness={:.0}", self.population.nets[0].fitness_info.fitness));
+            }
+        }
+        #[cfg(feature = "progress")]
+        if let Some(progress_bar) = progress_bar { progress_bar.finish(); }
+        self.summary.champions = self.stashed_nets.len() - champions_before;
+        self.summary.best = self.max_info;
+        Ok(self.summary.clone())
+    }
+
+    /// Shows (or hides, e.g. for `--quiet`) the progress bar over the generations.  It's also
+    /// hidden whenever stderr isn't a terminal.
+    #[cfg(feature = "progress")]
+    pub fn set_show_progress(&mut self, show_progress: bool) {
+        self.show_progress = show_progress;
+    }
+
+    #[cfg(feature = "progress")]
+    fn new_progress_bar(&self) -> Option<indicatif::ProgressBar> {
+        use std::io::IsTerminal;
+        if !self.show_progress || !std::io::stderr().is_terminal() { return None; }
+        let progress_bar = indicatif::ProgressBar::new(self.my_meta.max_generations as u64);
+        progress_bar.set_style(indicatif::ProgressStyle::with_template("gen {pos}/{len} [{bar:40}] ETA {eta}  {msg}").unwrap().progress_chars("=> "));
+        Some(progress_bar)
+    }
+
+    /// A flag that, once set (e.g. from a Ctrl-C handler), makes `run_x_generations()` write a
+    /// `Checkpoint` into the checkpoint dir and return before starting another generation.
+    pub fn stop_requested(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop_requested)
+    }
+
+    /// Picks up the run that wrote the `Checkpoint` at `path`: its population and best fitness are
+    /// restored, `run_x_generations()` continues from the generation it stopped before, and new
+    /// ids continue past the ones its nets use.
+    pub fn resume_from_checkpoint(&mut self, path: &str) -> Result<(), String> {
+        let checkpoint = Checkpoint::from_file(path)?;
+        checkpoint.id_registry.restore();
+        self.population.nets = checkpoint.nets;
+        self.max_info = checkpoint.max_info;
+        self.next_generation = checkpoint.generation;
+        Ok(())
+    }
+
+    /// Where a stopped run's `Checkpoint` is written; `stash/` by default.
+    pub fn set_checkpoint_dir(&mut self, dir: PathBuf) {
+        self.checkpoint_dir = dir;
+    }
+
+    /// Whether each new champion also gets a PNG snapshot of its last game's final board in
+    /// `stash/`, named like its `write_champion_files()` file.
+    pub fn set_champion_snapshots(&mut self, enabled: bool) {
+        self.champion_snapshots = enabled;
+    }
+
+    /// Whether each new champion also gets a JSON trace of its last game in `stash/`: the inputs,
+    /// outputs, and move of every step (see `trace_playback()`), for diagnosing what it senses.
+    pub fn set_champion_traces(&mut self, enabled: bool) {
+        self.champion_traces = enabled;
+    }
+
+    fn write_checkpoint(&self, generation: usize, path: &Path) -> std::io::Result<()> {
+        let checkpoint = Checkpoint {
+            generation,
+            max_info: self.max_info,
+            id_registry: IdRegistry::current(),
+            nets: self.population.nets.clone(),
+        };
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &checkpoint)?;
+        writer.flush()
+    }
+
+    /// When set, every generation's full population fitness is written as a CSV file into `dir`.
+    pub fn set_dump_fitness_dir(&mut self, dir: Option<PathBuf>) {
+        self.dump_fitness_dir = dir;
+    }
+
+    /// When set, every generation's `GenerationStats` is appended to the JSON Lines file at `path`
+    /// (created if need be), one flushed line per generation, so it can be followed as it grows.
+    pub fn set_metrics_jsonl(&mut self, path: Option<&Path>) -> std::io::Result<()> {
+        self.metrics_jsonl = match path {
+            None => None,
+            Some(path) => {
+                if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) { std::fs::create_dir_all(dir)?; }
+                Some(BufWriter::new(std::fs::OpenOptions::new().create(true).append(true).open(path)?))
             }
+        };
+        Ok(())
+    }
+
+    fn write_metrics_line(writer: &mut impl Write, stats: &GenerationStats) -> std::io::Result<()> {
+        serde_json::to_writer(&mut *writer, stats)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    /// Writes one row per net: its id, fitness, apples, visited, moves, and node/connection counts.
+    fn dump_population_fitness(nets: &[Net<MyFitnessInfo>], path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "id,fitness,apples,visited,moves,nodes,connections")?;
+        for net in nets {
+            let info = &net.fitness_info;
+            writeln!(writer, "{},{},{},{},{},{},{}", net.id.0, info.fitness, info.apples, info.visited, info.moves, net.node_count(), net.connection_count())?;
+        }
+        writer.flush()
+    }
+
+    /// How many nets ate each (rounded, since it's averaged over games) number of apples.
+    fn apple_histogram(nets: &[Net<MyFitnessInfo>]) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for net in nets {
+            *histogram.entry(net.fitness_info.apples.round().max(0.0) as usize).or_insert(0) += 1;
         }
+        histogram
     }
 
     pub fn run_one_generation(&mut self, generation: usize, era_info: &EraInfo, games_played_for_fitness: usize) {
         let multiplier = 1.0 + era_info.eras as f64;
         let pop  = &mut self.population;
         let game = &mut self.game;
+        let my_meta = &self.my_meta;
+        let novelty_archive = &self.novelty_archive;
+        let champion_snapshots = self.champion_snapshots;
+        let champion_traces = self.champion_traces;
+        let mut most_novel_behavior: Option<(f32, Vec<f32>)> = None;
         let mut global_max_fitness_info = self.max_info;
-        pop.run_one_generation(multiplier, |net| {
+        let mut earlier_games = Vec::new();
+        // The steps of `Population::run_one_generation()`, so the evaluated population can be dumped
+        pop.create_initial_population();
+        pop.evaluate_population(|net| {
             // If we've already computed this Net's fitness, just use that, unless...
             if net.fitness_info.fitness != f32::MIN { 
                 // ...unless it's an era boundary, in which case the fitness function might
-                // change, so let's re-evaluate then.
-                if era_info.is_era_boundary {
+                // change, so let's re-evaluate then.  With seeded boards, it was scored on last
+                // generation's boards, so re-evaluate to rank it on the same boards as the rest.
+                if era_info.is_era_boundary || my_meta.seeded_boards {
                     net.fitness_info.fitness = f32::MIN;
                 } else {
                     return net.fitness_info;
                 }
             }
-            let mut max_single_game_fitness_info = MyFitnessInfo::default();
-            let mut sum_fitnesses_info = MyFitnessInfo { fitness: 0.0, ..Default::default() };
-            for _ in 0..games_played_for_fitness {
-                let single_game_fitness_info = Self::run_one_game(net, game, era_info);
-                if max_single_game_fitness_info.fitness < single_game_fitness_info.fitness { 
-                    max_single_game_fitness_info = single_game_fitness_info; 
+            let mut final_net_fitness_info = Self::play_games(net, game, era_info, my_meta, generation, games_played_for_fitness, &mut earlier_games);
+            if era_info.fitness_kind == EraFitness::Novelty {
+                // Scored by how its last game differs from what's been seen before
+                let behavior = novelty::behavior_of_game(game);
+                let novelty = novelty_archive.novelty(&behavior, my_meta.novelty_neighbors.unwrap_or(1));
+                final_net_fitness_info.fitness += NOVELTY_SCALE * novelty;
+                if !matches!(&most_novel_behavior, Some((most, _)) if *most >= novelty) {
+                    most_novel_behavior = Some((novelty, behavior));
                 }
-                sum_fitnesses_info += &single_game_fitness_info;
             }
-            let ave_fitness_info = sum_fitnesses_info * (1.0 / games_played_for_fitness as f32);
-            let final_net_fitness_info = max_single_game_fitness_info * 0.75 + ave_fitness_info * 0.25;
             net.fitness_info = final_net_fitness_info;
-            if generation != 0 && global_max_fitness_info.fitness < final_net_fitness_info.fitness {
-                println!("New Max  gen={generation}: {}: fitness={final_net_fitness_info}; max={max_single_game_fitness_info}    multiplier={multiplier}", net.id);
-                global_max_fitness_info = final_net_fitness_info;
-                self.stashed_nets.push(StashInfo { 
-                    net: net.clone(), 
-                    generation,
-                });
-                match serde_json::to_string_pretty(&net) {
-                    Err(e) => { println!("ERROR serializing Net to JSON: {e:#?}"); panic!() }
-                    Ok(s) => {
-                        let gen = generation;
-                        let apples = final_net_fitness_info.apples;
-                        let fitness = final_net_fitness_info.fitness;
-                        let date = chrono::Local::now().format("%Y%m%d");
-                        let filename = format!("stash/Net-{date}-Gen{gen}-Apples{apples}-Fit{fitness:.0}.json");
-                        let mut file = File::create(filename).unwrap();
-                        file.write_all(s.as_bytes()).unwrap();
+            if generation != 0
+                && Self::check_for_new_champion(&mut global_max_fitness_info, &mut self.stashed_nets, &mut self.on_new_champion, net, &final_net_fitness_info, &earlier_games, &game.playback, generation) {
+                let stem = Self::champion_file_stem(&final_net_fitness_info, generation);
+                if champion_snapshots {
+                    let path = PathBuf::from(format!("stash/{stem}.png"));
+                    if let Err(e) = snapshot::write_grid_png(&game.grid, &path) {
+                        error!("ERROR writing snapshot: {e}");
                     }
                 }
-                match serde_json::to_string_pretty(&game.playback) {
-                    Err(e) => { println!("ERROR serializing Playback to JSON: {e:#?}"); panic!() }
-                    Ok(s) => {
-                        let gen = generation;
-                        let apples = final_net_fitness_info.apples;
-                        let fitness = final_net_fitness_info.fitness;
-                        let date = chrono::Local::now().format("%Y%m%d");
-                        let filename = format!("stash/Net-{date}-Gen{gen}-Apples{apples}-Fit{fitness:.0}-Playback.json");
-                        let mut file = File::create(filename).unwrap();
-                        file.write_all(s.as_bytes()).unwrap();
+                if champion_traces {
+                    // Re-run its game in trace mode, rather than paying to capture every game
+                    let path = PathBuf::from(format!("stash/{stem}-Trace.json"));
+                    if let Err(e) = Self::write_trace(&Self::trace_playback(net, &game.playback, my_meta), &path) {
+                        error!("ERROR writing {}: {e}", path.display());
                     }
                 }
             }
             final_net_fitness_info
         });
         self.max_info = global_max_fitness_info;
+        if let Some((_, behavior)) = most_novel_behavior {
+            self.novelty_archive.add(behavior);
+        }
+        if let Some(dir) = &self.dump_fitness_dir {
+            let path = dir.join(format!("Fitness-Gen{generation:06}.csv"));
+            if let Err(e) = Self::dump_population_fitness(&self.population.nets, &path) {
+                error!("ERROR writing {}: {e}", path.display());
+            }
+        }
+        if let Some(writer) = &mut self.metrics_jsonl {
+            let stats = GenerationStats::new(generation, era_info, &self.events_fired, &self.population.nets);
+            if let Err(e) = Self::write_metrics_line(writer, &stats) {
+                error!("ERROR writing metrics: {e}");
+            }
+        }
+        if self.my_meta.apple_histogram_every.is_some_and(|every| generation % every == 0) {
+            let histogram = Self::apple_histogram(&self.population.nets);
+            let max_apples = histogram.last_key_value().map_or(0, |(&apples, _)| apples);
+            let buckets = histogram.iter().map(|(apples, count)| format!("{apples}:{count}")).collect::<Vec<_>>().join(" ");
+            sim_info!("Apples gen={generation}: max={max_apples} | {buckets}");
+        }
+        let era_mutation_params = self.mutation_params_for(era_info.fitness_kind).clone();
+        let base_mutation_params = std::mem::replace(&mut self.population.population_params.mutation_params, era_mutation_params);
+        self.population.create_next_generation(multiplier);
+        self.population.population_params.mutation_params = base_mutation_params;
+    }
+
+    /// The MutationParams the next generation is bred with during an era of `fitness_kind`.
+    fn mutation_params_for(&self, fitness_kind: EraFitness) -> &MutationParams {
+        self.my_meta.era_mutation_params[fitness_kind as usize].as_ref().unwrap_or(&self.population.population_params.mutation_params)
+    }
+
+    /// If `fitness_info` beats `global_max_fitness_info`, records the new max, stashes the net,
+    /// and invokes the `on_new_champion` callback (if any).  Returns true for a new champion.
+    fn check_for_new_champion(
+        global_max_fitness_info: &mut MyFitnessInfo, 
+        stashed_nets: &mut Vec<StashInfo>, 
+        on_new_champion: &mut Option<ChampionCallback>,
+        net: &Net<MyFitnessInfo>, 
+        fitness_info: &MyFitnessInfo, 
+        earlier_games: &[Playback],
+        playback: &Playback, 
+        generation: usize,
+    ) -> bool {
+        if global_max_fitness_info.fitness >= fitness_info.fitness { return false; }
+        sim_info!("New Max  gen={generation}: {}: fitness={fitness_info}", net.id);
+        *global_max_fitness_info = *fitness_info;
+        stashed_nets.push(StashInfo { 
+            net: net.clone(), 
+            generation,
+        });
+        if let Some(callback) = on_new_champion.as_mut() {
+            callback(net, fitness_info, earlier_games, playback, generation);
+        }
+        true
+    }
+
+    /// The default `on_new_champion` callback: writes a `ChampionRecord` of the Net and the
+    /// Playbacks of its games into the `stash/` directory.
+    pub fn write_champion_files(net: &Net<MyFitnessInfo>, fitness_info: &MyFitnessInfo, earlier_games: &[Playback], playback: &Playback, generation: usize) {
+        let record = ChampionRecord { net: net.clone(), playback: playback.clone(), earlier_games: earlier_games.to_vec(), generation, fitness_info: *fitness_info };
+        match serde_json::to_string_pretty(&record) {
+            Err(e) => { error!("ERROR serializing ChampionRecord to JSON: {e:#?}"); panic!() }
+            Ok(s) => {
+                let filename = format!("stash/{}.json", Self::champion_file_stem(fitness_info, generation));
+                let mut file = File::create(filename).unwrap();
+                file.write_all(s.as_bytes()).unwrap();
+            }
+        }
+    }
+
+    /// The name, without extension, of a champion's files in `stash/`.
+    fn champion_file_stem(fitness_info: &MyFitnessInfo, generation: usize) -> String {
+        let gen = generation;
+        let apples = fitness_info.apples;
+        let fitness = fitness_info.fitness;
+        let date = chrono::Local::now().format("%Y%m%d");
+        format!("Net-{date}-Gen{gen}-Apples{apples}-Fit{fitness:.0}")
+    }
+
+    /// Loads a Net saved by `write_champion_files()` (or a bare Net) and describes it: its
+    /// structure, and its inputs, outputs, and chosen move on a new game's board, or on the board
+    /// at the end of the playback in `playback_path`.
+    pub fn inspect_net(net_path: &str, playback_path: Option<&str>) -> Result<String, String> {
+        let net = Self::load_net(net_path)?;
+        let game = match playback_path {
+            None => SnakeGame::new(None),
+            Some(path) => {
+                let playback = Playback::from_file(path)?;
+                SnakeGame::from_playback_with_config(&playback.config, &playback.playback_events)
+            }
+        };
+        Self::describe_net_on(&net, &game).map_err(|e| format!("{net_path}: {e}"))
+    }
+
+    /// Loads a Net as `inspect_net()` does, and times `iterations` evaluations of it (on the same
+    /// random inputs each time).
+    pub fn bench_net(net_path: &str, iterations: usize) -> Result<String, String> {
+        let mut net = Self::load_net(net_path)?;
+        net.build_evaluation_order();
+        let mut rng = thread_rng();
+        let inputs = (0..net.net_params.input_count).map(|_| rng.gen_range(-1.0..1.0)).collect::<Vec<f32>>();
+        let start = std::time::Instant::now();
+        for _ in 0..iterations {
+            net.set_inputs(&inputs);
+            net.evaluate();
+        }
+        let elapsed = start.elapsed();
+        let each = elapsed / iterations.max(1) as u32;
+        Ok(format!("{net_path}: {} nodes, {} connections; {iterations} evaluations in {elapsed:.2?} ({each:.2?} each)\n", net.node_count(), net.connection_count()))
+    }
+
+    /// Loads a Net saved by `write_champion_files()`, or a bare Net.
+    pub fn load_net(path: &str) -> Result<Net<MyFitnessInfo>, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        match serde_json::from_str::<ChampionRecord>(&s) {
+            Ok(record) => Ok(record.net),
+            Err(_) => serde_json::from_str(&s).map_err(|e| format!("parsing {path}: {e}")),
+        }
+    }
+
+    /// Describes `net`: its structure, and its inputs, outputs, and chosen move on `game`'s board.
+    pub fn describe_net_on(net: &Net<MyFitnessInfo>, game: &SnakeGame) -> Result<String, String> {
+        use std::fmt::Write;
+        let input_encoder = InputEncoder::ALL.into_iter().find(|encoder| encoder.input_count() == net.net_params.input_count);
+        let Some(input_encoder) = input_encoder.filter(|_| net.net_params.output_count == NUM_OUTPUTS) else {
+            return Err(format!("the net has {} inputs and {} outputs, which no InputEncoder matches", net.net_params.input_count, net.net_params.output_count));
+        };
+        let mut net = net.clone();
+        let input_names: &'static [&'static str] = Box::leak(input_encoder.feature_names().into_boxed_slice());
+        net.net_params.input_names  = Some(input_names);
+        net.net_params.output_names = Some(&OUTPUT_NAMES);
+
+        let inputs = input_encoder.encode(game, BoardTransform::Identity, InputEncoder::DEFAULT_RANGE, None);
+        net.build_evaluation_order();
+        net.set_inputs(&inputs);
+        net.evaluate();
+        let mut report = String::new();
+        writeln!(report, "{}: fitness={}", net.id, net.fitness_info).unwrap();
+        writeln!(report, "{}", net.structure_string()).unwrap();
+        writeln!(report, "Inputs:").unwrap();
+        for (name, value) in input_names.iter().zip(inputs) { writeln!(report, "  {name:<10} = {value:.4}").unwrap(); }
+        writeln!(report, "Outputs:").unwrap();
+        for (name, value) in net.describe_outputs() { writeln!(report, "  {name:<10} = {value:.4}").unwrap(); }
+        writeln!(report, "Chosen move: {:?}", Self::interpret_outputs(&net, OutputPolicy::Argmax, &mut thread_rng())).unwrap();
+        Ok(report)
+    }
+
+    /// Replaces the callback invoked whenever a new champion (new maximum fitness) is found.
+    /// Pass `None` to disable it, e.g. to avoid writing into `stash/`.
+    pub fn set_on_new_champion(&mut self, callback: Option<ChampionCallback>) {
+        self.on_new_champion = callback;
+    }
+
+    /// Uses `write_champion_files()` as the `on_new_champion` callback, but with the saved net's
+    /// weights rounded to `decimals` decimal places (see `Net::quantize_weights()`).
+    pub fn set_champion_weight_decimals(&mut self, decimals: u32) {
+        self.on_new_champion = Some(Box::new(move |net, fitness_info, earlier_games, playback, generation| {
+            let mut net = net.clone();
+            net.quantize_weights(decimals);
+            Self::write_champion_files(&net, fitness_info, earlier_games, playback, generation);
+        }));
+    }
+
+    /// Replays `playback`'s game (as played by `net`), capturing what `net` saw and output before
+    /// each move.  The game's moves are followed as recorded, so there's exactly one step per move.
+    /// With several `board_transforms`, the game was played through the last one.
+    pub fn trace_playback(net: &Net<MyFitnessInfo>, playback: &Playback, my_meta: &MyMetaParams) -> Vec<TraceStep> {
+        let mut net = net.clone();
+        net.build_evaluation_order();
+        let transform = *my_meta.board_transforms.last().unwrap_or(&BoardTransform::Identity);
+        let era_info = EraInfo { generations: 0, eras: 0, is_era_boundary: false, is_end_special_fitness: false, fitness_kind: EraFitness::Normal };
+        let mut game = SnakeGame::from_playback_with_config(&playback.config, playback.prefix_through_move(0));
+        game.starvation_limit = my_meta.starvation_limit;
+        let mut moves = recorded_moves(playback);
+        let mut trace = Vec::with_capacity(playback.move_count());
+        Self::play_and_score(&mut game, &era_info, my_meta, |game| {
+            let (direction, new_apple_location) = moves.next()?;
+            let inputs = my_meta.input_encoder.encode(game, transform, my_meta.input_range, my_meta.sensing_range);
+            net.set_inputs(&inputs);
+            net.evaluate();
+            trace.push(TraceStep { inputs, outputs: net.get_outputs(), direction });
+            Some((direction, new_apple_location))
+        });
+        trace
+    }
+
+    fn write_trace(trace: &[TraceStep], path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, trace)?;
+        writer.flush()
+    }
+
+    /// Plays `net` the `games` games that decide its fitness in `generation`, and aggregates them.
+    /// `game` is left holding the last one, and `earlier_games` the playbacks of the rest.
+    fn play_games(net: &mut Net<MyFitnessInfo>, game: &mut SnakeGame, era_info: &EraInfo, my_meta: &MyMetaParams, generation: usize, games: usize, earlier_games: &mut Vec<Playback>) -> MyFitnessInfo {
+        let mut play_fitness_infos = Vec::with_capacity(games * my_meta.board_transforms.len());
+        earlier_games.clear();
+        for game_index in 0..games {
+            for &transform in my_meta.board_transforms.iter() {
+                if !play_fitness_infos.is_empty() { earlier_games.push(std::mem::take(&mut game.playback)); }
+                play_fitness_infos.push(if my_meta.seeded_boards {
+                    Self::run_one_seeded_game(net, game, era_info, my_meta, transform, Self::board_seed(generation, game_index))
+                } else {
+                    Self::run_one_game(net, game, era_info, my_meta, transform)
+                });
+            }
+        }
+        Self::aggregate_plays(&play_fitness_infos, my_meta)
+    }
+
+    /// Aggregates the fitness infos of a net's plays (each game once per board transform, in
+    /// order): each game's transforms are averaged, then the games are aggregated.
+    fn aggregate_plays(play_fitness_infos: &[MyFitnessInfo], my_meta: &MyMetaParams) -> MyFitnessInfo {
+        let transforms = my_meta.board_transforms.len();
+        let game_fitness_infos = play_fitness_infos.chunks(transforms).map(|plays| {
+            let mut single_game_fitness_info = MyFitnessInfo { fitness: 0.0, ..Default::default() };
+            for play in plays { single_game_fitness_info += play; }
+            single_game_fitness_info * (1.0 / transforms as f32)
+        }).collect::<Vec<_>>();
+        my_meta.fitness_aggregation.aggregate(&game_fitness_infos)
+    }
+
+    /// The seed of the `game_index`-th board that every net plays in `generation`, with
+    /// `MyMetaParams::seeded_boards`.
+    fn board_seed(generation: usize, game_index: usize) -> u64 {
+        ((generation as u64) << 32) | game_index as u64
+    }
+
+    /// Like `run_one_game()`, but on the `SnakeGame::from_seed()` board for `seed`, rather than a
+    /// random one.  Input dropout and output sampling are seeded too, so replaying it is exact.
+    fn run_one_seeded_game(net: &mut Net<MyFitnessInfo>, game: &mut SnakeGame, era_info: &EraInfo, my_meta: &MyMetaParams, transform: BoardTransform, seed: u64) -> MyFitnessInfo {
+        let config = GameConfig { starvation_limit: my_meta.starvation_limit, initial_to_grow: my_meta.initial_to_grow, ..Default::default() };
+        *game = SnakeGame::from_seed_with_config(&config, seed);
+        Self::play_net(net, game, era_info, my_meta, transform, my_meta.input_dropout, &mut Self::play_rng(seed))
     }
 
-    pub fn run_one_game(net: &mut Net<MyFitnessInfo>, game: &mut SnakeGame, era_info: &EraInfo) -> MyFitnessInfo {
+    /// The rng for a net's own random draws in the game on board `seed`, apart from the board's.
+    fn play_rng(seed: u64) -> StdRng {
+        StdRng::seed_from_u64(!seed)
+    }
+
+    /// Plays one game, with the net seeing the board through `transform` (e.g. mirrored).
+    pub fn run_one_game(net: &mut Net<MyFitnessInfo>, game: &mut SnakeGame, era_info: &EraInfo, my_meta: &MyMetaParams, transform: BoardTransform) -> MyFitnessInfo {
+        game.starvation_limit = my_meta.starvation_limit;
+        game.initial_to_grow = my_meta.initial_to_grow;
         game.restart(None);
+        Self::play_net(net, game, era_info, my_meta, transform, my_meta.input_dropout, &mut thread_rng())
+    }
+
+    /// Like `run_one_game()`, but plays `game` from where it is, rather than from a restart, with
+    /// any input dropout and output sampling drawn from `rng`.  Only training passes an
+    /// `input_dropout` rate.
+    fn play_net(net: &mut Net<MyFitnessInfo>, game: &mut SnakeGame, era_info: &EraInfo, my_meta: &MyMetaParams, transform: BoardTransform, input_dropout: Option<f64>, rng: &mut impl Rng) -> MyFitnessInfo {
+        Self::play_and_score(game, era_info, my_meta, |game| {
+            let mut inputs = my_meta.input_encoder.encode(game, transform, my_meta.input_range, my_meta.sensing_range);
+            if let Some(rate) = input_dropout { Self::drop_out_inputs(&mut inputs, rate, rng); }
+            net.set_inputs(&inputs);
+            net.evaluate();
+            Some((transform.to_board_direction(Self::interpret_outputs(net, my_meta.output_policy, rng)), None))
+        })
+    }
+
+    /// Zeroes each of `inputs` with probability `rate`, as if that sensor had failed this move.
+    fn drop_out_inputs(inputs: &mut [f32], rate: f64, rng: &mut impl Rng) {
+        for input in inputs.iter_mut() {
+            if rng.gen_bool(rate) { *input = 0.0; }
+        }
+    }
+
+    /// Plays `net` on one `SnakeGame::from_seed()` board per seed, to tell a genuinely good net from
+    /// one that got lucky with its training games.
+    pub fn evaluate_robustness(&self, net: &Net<MyFitnessInfo>, seeds: &[u64]) -> RobustnessReport {
+        let mut net = net.clone();
+        let era_info = EraInfo { generations: 0, eras: 0, is_era_boundary: false, is_end_special_fitness: false, fitness_kind: EraFitness::Normal };
+        let mut apples = Vec::with_capacity(seeds.len());
+        let (mut died, mut move_limited) = (0, 0);
+        for &seed in seeds {
+            let config = GameConfig { starvation_limit: self.my_meta.starvation_limit, ..Default::default() };
+            let mut game = SnakeGame::from_seed_with_config(&config, seed);
+            Self::play_net(&mut net, &mut game, &era_info, &self.my_meta, BoardTransform::Identity, None, &mut Self::play_rng(seed));
+            apples.push(game.apples_eaten);
+            if game.state == GameState::Running { move_limited += 1; } else { died += 1; }
+        }
+        RobustnessReport::new(&apples, died, move_limited)
+    }
+
+    /// A cheap summary of how `net` plays: its first `FINGERPRINT_MOVES` (argmax) moves on a fixed
+    /// board, 3 bits each, with 0s once the game is over.  Nets that play alike share a fingerprint.
+    pub fn behavior_fingerprint(net: &mut Net<MyFitnessInfo>, my_meta: &MyMetaParams) -> u64 {
+        net.build_evaluation_order();
+        let mut game = SnakeGame::from_seed(FINGERPRINT_SEED);
+        let mut fingerprint = 0;
+        for i in 0..FINGERPRINT_MOVES {
+            if game.state != GameState::Running { break; }
+            net.set_inputs(&my_meta.input_encoder.encode(&game, BoardTransform::Identity, my_meta.input_range, my_meta.sensing_range));
+            net.evaluate();
+            let dir = Self::interpret_outputs(net, OutputPolicy::Argmax, &mut thread_rng());
+            fingerprint |= (dir.to_index() as u64 + 1) << (3 * i);
+            game.move_snake(dir, None);
+        }
+        fingerprint
+    }
+
+    /// How many different `behavior_fingerprint()`s the population has: a behavioral-diversity
+    /// gauge, where a collapse to a few means it has converged.
+    fn distinct_behavior_count(&mut self) -> usize {
+        let my_meta = &self.my_meta;
+        self.population.nets.iter_mut().map(|net| Self::behavior_fingerprint(net, my_meta)).collect::<HashSet<_>>().len()
+    }
+
+    /// Plays `game` until it ends (or should be stopped), with `next_move` giving each move and,
+    /// optionally, where the apple goes if that move eats it.  `None` ends the game early.
+    fn play_and_score(game: &mut SnakeGame, era_info: &EraInfo, my_meta: &MyMetaParams, mut next_move: impl FnMut(&SnakeGame) -> Option<(Direction, Option<GridPoint>)>) -> MyFitnessInfo {
         let mut moves = 0_usize;
+        let mut detour_tracker = DetourTracker::new(game);
+        let mut trap_tracker = TrapTracker::new(my_meta.trap_penalty);
         while game.state == GameState::Running {
-            Self::collect_and_apply_inputs(net, game);
-            net.evaluate();
-            let dir = Self::interpret_outputs(net);
+            let Some((dir, new_apple_location)) = next_move(game) else { break; };
             let apples_before = game.apples_eaten;
-            game.move_snake(dir, None);
-            if apples_before != game.apples_eaten { game.clear_visited(); }
+            game.move_snake(dir, new_apple_location);
+            let has_eaten = apples_before != game.apples_eaten;
+            detour_tracker.after_move(game, has_eaten);
+            trap_tracker.after_move(game);
+            if has_eaten { game.clear_visited(); }
             moves += 1;
-            // Bail early if nothing is happening for too long
-            if moves > 500 + game.points_visited + apples_before * (1 + SnakeGame::GROW_INCREMENT) { break; }
+            if Self::should_stop_game(game, moves, apples_before, my_meta.bail_early) { break; }
         }
+        let length_weight = my_meta.length_weights[era_info.fitness_kind as usize];
+        let mut fitness_info = Self::fitness_info_of_game(era_info, game, moves, &detour_tracker, my_meta.apple_value_decay, length_weight);
+        let efficiency_weight = my_meta.efficiency_weights[era_info.fitness_kind as usize];
+        fitness_info.fitness += efficiency_weight * Self::path_efficiency(game.points_visited, moves) - trap_tracker.penalty();
+        fitness_info
+    }
+
+    /// How efficiently the snake explored: the fraction of its moves that entered a cell it hadn't
+    /// visited (since the last apple).  1.0 for never revisiting; near 0.0 for pacing in place.
+    fn path_efficiency(visited: usize, moves: usize) -> f32 {
+        if moves == 0 { return 0.0; }
+        visited as f32 / moves as f32
+    }
+
+    /// Whether to end a still-running game after `moves` moves: at `MAX_MOVES_PER_GAME`, or, when
+    /// `bail_early`, once nothing has happened for too long.
+    fn should_stop_game(game: &SnakeGame, moves: usize, apples_before: usize, bail_early: bool) -> bool {
+        if moves >= MAX_MOVES_PER_GAME { return true; }
+        bail_early && moves > 500 + game.points_visited + apples_before * (1 + game.grow_increment)
+    }
+
+    fn fitness_info_of_game(era_info: &EraInfo, game: &SnakeGame, moves: usize, detour_tracker: &DetourTracker, apple_value_decay: Option<f32>, length_weight: f32) -> MyFitnessInfo {
         // Fitness now includes # unique squares visited, where what's considered unique
         // gets reset every apple (so points_visited is monotonically increasing).
         let apples  = game.apples_eaten;
         let visited = game.points_visited;
+        let detour  = detour_tracker.detour;
+        let apple_value = detour_tracker.apple_value(apple_value_decay);
         MyFitnessInfo { 
-            fitness: Self::compute_fitness(era_info, apples, visited, moves),
-            apples:  apples  as f32,
-            visited: visited as f32,
-            moves:   moves   as f32,
+            fitness:  Self::compute_fitness(era_info, apple_value, apples, visited, moves, detour) + length_weight * game.snake.length() as f32,
+            apples:   apples  as f32,
+            visited:  visited as f32,
+            moves:    moves   as f32,
+            coverage: game.coverage(),
+            detour:   detour  as f32,
         }
     }
 
 
     // TODO: Consider keeping separate set of MAX values for each EraFitness value.
-    fn compute_fitness(era_info: &EraInfo, apples: usize, visited: usize, moves: usize) -> f32 {
-        let apples  = apples  as f32;   // Typical max is 9
+    fn compute_fitness(era_info: &EraInfo, apple_value: f32, apples: usize, visited: usize, moves: usize, detour: usize) -> f32 {
+        let apples  = apples  as f32;   // Typical max is 9; apple_value is at most this
         let visited = visited as f32;   // Typical max is 1000
         let moves   = moves   as f32;   // Typical max is 1300
+        let detour  = detour  as f32;
         let excess_moves = moves - visited;
         match era_info.fitness_kind {
             EraFitness::Normal => {
-                // The "normal" fitness function
-                10_000.0 * apples
+                // The "normal" fitness function.  Detour outweighs visited, so that wandering
+                // around on the way to an apple doesn't pay.
+                10_000.0 * apple_value
                 +    1.0 * visited
                 -    0.1 * (excess_moves / (apples + 1.0))
+                -    5.0 * detour
             }
             EraFitness::FavorVisits => {
                 // Favor visiting new spaces
-                1_000.0 * apples
+                1_000.0 * apple_value
                 +  40.0 * visited
                 -   1.0 * excess_moves
             }
             EraFitness::FavorMoves => {
                 // Favor moves
-                1_000.0 * apples
+                1_000.0 * apple_value
                 +  30.0 * moves
             }
+            // Scored by novelty instead, once the whole game's behavior is known (see `run_one_generation()`)
+            EraFitness::Novelty => 0.0,
         }
     }
     
-    fn interpret_outputs(net: &Net<MyFitnessInfo>) -> Direction {
+    /// The move `net`'s outputs choose under `output_policy`, which draws from `rng` if it samples.
+    fn interpret_outputs(net: &Net<MyFitnessInfo>, output_policy: OutputPolicy, rng: &mut impl Rng) -> Direction {
         let outputs = net.get_outputs();
+        // NaN outputs (e.g. from exploding weights) can't be compared; with nothing to go on, go North
+        if outputs.iter().all(|v| v.is_nan()) { return Self::output_direction(0); }
+        let outputs = outputs.into_iter().map(|v| if v.is_nan() { f32::MIN } else { v }).collect::<Vec<_>>();
+        let i = match output_policy {
+            OutputPolicy::Argmax => Self::argmax(&outputs),
+            OutputPolicy::SoftmaxSample { temperature } => Self::softmax_sample(&outputs, temperature, rng),
+        };
+        Self::output_direction(i)
+    }
+
+    /// The move that output `i` stands for, as `OUTPUT_NAMES` says.  Nets stashed before output 3
+    /// meant West read it as East, so they can play differently now.
+    fn output_direction(i: usize) -> Direction {
+        Direction::from_index(i)
+    }
+
+    fn argmax(outputs: &[f32]) -> usize {
         let mut i_max = 0;
         let mut v_max = f32::MIN;
         for (i, &v) in outputs.iter().enumerate() {
@@ -403,38 +1440,22 @@ impl NnPlaysSnake {
                 i_max = i;
             }
         }
-        match i_max {
-            0 => Direction::North,
-            1 => Direction::East,
-            2 => Direction::South,
-            3 => Direction::East,
-            _ => panic!(),
-        }
-    }
-
-    fn collect_and_apply_inputs(net: &mut Net<MyFitnessInfo>, game: &SnakeGame) {
-        let (wall_dist, snake_dist) = game.wall_and_body_distances();
-        let pt_snake_head = game.snake.head_location;
-        let pt_apple = game.apple.location;
-        let snake_length = game.snake.length();
-
-        // Normalized inputs
-        let inputs: [f32; NUM_INPUTS] = [
-            wall_dist[0] as f32 / 40.0,
-            wall_dist[1] as f32 / 40.0,
-            wall_dist[2] as f32 / 40.0,
-            wall_dist[3] as f32 / 40.0,
-            snake_dist[0] as f32 / 40.0,
-            snake_dist[1] as f32 / 40.0,
-            snake_dist[2] as f32 / 40.0,
-            snake_dist[3] as f32 / 40.0,
-            (pt_snake_head.x - pt_apple.x) as f32 / 35.0,   // Max distance = RMS(30,40) = 35.36
-            (pt_snake_head.y - pt_apple.y) as f32 / 35.0,
-            snake_length as f32 / 1200.0, 
-            1.0
-        ];
-        net.set_inputs(&inputs);
+        i_max
+    }
+
+    fn softmax_sample(outputs: &[f32], temperature: f32, rng: &mut impl Rng) -> usize {
+        if temperature <= f32::EPSILON { return Self::argmax(outputs); }
+        // Subtract the max before exp() so large outputs / small temperatures can't overflow.
+        let v_max = outputs[Self::argmax(outputs)];
+        let weights = outputs.iter().map(|&v| ((v - v_max) / temperature).exp()).collect::<Vec<_>>();
+        let mut r = rng.gen::<f32>() * weights.iter().sum::<f32>();
+        for (i, &w) in weights.iter().enumerate() {
+            if r < w { return i; }
+            r -= w;
+        }
+        Self::argmax(outputs)  // Only reachable through rounding error
     }
+
     
 
     // EVENTS
@@ -447,31 +1468,769 @@ impl NnPlaysSnake {
         match era_info.eras {
             4 => self.event_cataclism_remove_fewest_visited(),
             5 => self.event_cataclism_remove_fewest_apples(),
-            8 => self.event_resurrect_maxes(),
+            8 => match self.my_meta.resurrection_jitter {
+                None        => self.event_resurrect_maxes(),
+                Some(sigma) => self.event_resurrect_jittered(sigma),
+            },
             _ => {},
         }
     }
 
     fn event_cataclism_remove_fewest_visited(&mut self) {
-        println!("XXXXXX CATACLISM: Remove fewest visited XXXXXXXXXXXXXXXXXXXXXXXX");
+        sim_info!("XXXXXX CATACLISM: Remove fewest visited XXXXXXXXXXXXXXXXXXXXXXXX");
+        self.summary.cataclysms += 1;
+        self.events_fired.push("cataclysm_fewest_visited");
         let visited_max = self.population.nets.iter().map(|n| n.fitness_info.visited).reduce(|acc, v| if acc < v { v } else { acc }).unwrap();
         let visited_ave = self.population.nets.iter().map(|n| n.fitness_info.visited).sum::<f32>() / self.population.nets.len() as f32;
         let visited_benchmark = if thread_rng().gen_bool(0.5) { visited_max / 2.0 } else { visited_ave };
-        self.population.nets.retain(|n| n.fitness_info.visited > visited_benchmark );
+        self.cataclism_remove_at_or_below(visited_benchmark, |f| f.visited);
     }
     
     fn event_cataclism_remove_fewest_apples(&mut self) {
-        println!("XXXXXX CATACLISM: Remove fewest apples XXXXXXXXXXXXXXXXXXXXXXXX");
+        sim_info!("XXXXXX CATACLISM: Remove fewest apples XXXXXXXXXXXXXXXXXXXXXXXX");
+        self.summary.cataclysms += 1;
+        self.events_fired.push("cataclysm_fewest_apples");
         let apples_max = self.population.nets.iter().map(|n| n.fitness_info.apples).reduce(|acc, v| if acc < v { v } else { acc }).unwrap();
         let apples_ave = self.population.nets.iter().map(|n| n.fitness_info.apples).sum::<f32>() / self.population.nets.len() as f32;
         let apples_benchmark = if thread_rng().gen_bool(0.5) { apples_max / 2.0 } else { apples_ave };
-        self.population.nets.retain(|n| n.fitness_info.apples > apples_benchmark );
+        self.cataclism_remove_at_or_below(apples_benchmark, |f| f.apples);
+    }
+
+    /// Removes the nets whose `metric` is at or below `benchmark`, except that the population
+    /// never drops below min_population_after_cataclysm: the best of the condemned are spared
+    /// first, and if that's still not enough, the stash and then random nets make up the rest.
+    fn cataclism_remove_at_or_below(&mut self, benchmark: f32, metric: impl Fn(&MyFitnessInfo) -> f32) {
+        let nets = &mut self.population.nets;
+        nets.sort_by(|a, b| metric(&b.fitness_info).total_cmp(&metric(&a.fitness_info)));
+        let survivors = nets.iter().filter(|n| metric(&n.fitness_info) > benchmark).count();
+        let min_population = self.my_meta.min_population_after_cataclysm;
+        nets.truncate(survivors.max(min_population));
+        for sn in self.stashed_nets.iter() {
+            if nets.len() >= min_population { break; }
+            nets.push(sn.net.clone());
+        }
+        self.population.fill_with_random_nets(min_population);
     }
 
     fn event_resurrect_maxes(&mut self) {
-        println!("@@@@ RESURECTION!!! @@@@@@@@@@@@@@@@@");
+        sim_info!("@@@@ RESURECTION!!! @@@@@@@@@@@@@@@@@");
+        self.summary.resurrections += 1;
+        self.events_fired.push("resurrection");
         for sn in self.stashed_nets.iter() {
             self.population.nets.push(sn.net.clone());
         }
+        // A big stash would otherwise swell this generation's evaluation (and memory)
+        self.population.retain_best(self.my_meta.meta.population_size);
     }
-}
\ No newline at end of file
+
+    /// Like `event_resurrect_maxes()`, but brings back each stashed net as a copy with a new id and
+    /// Gaussian noise added to its weights, to explore around past champions instead of repeating them.
+    fn event_resurrect_jittered(&mut self, sigma: f32) {
+        sim_info!("@@@@ JITTERED RESURECTION!!! @@@@@@@@@@@@@@@@@");
+        self.summary.resurrections += 1;
+        self.events_fired.push("resurrection_jittered");
+        // The copies are yet to be evaluated, so make room for them first
+        self.population.retain_best(self.my_meta.meta.population_size.saturating_sub(self.stashed_nets.len()));
+        for sn in self.stashed_nets.iter() {
+            let mut net = sn.net.clone_as_new_net();
+            net.jitter_weights(sigma, &mut thread_rng());
+            self.population.nets.push(net);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::snake_game::{ApplePolicy, GameConfig, Grid, GridPoint, Level, PlaybackEvents};
+
+    use super::*;
+
+    fn test_net() -> Net<MyFitnessInfo> {
+        Net::new(NetParams { input_count: InputEncoder::Standard.input_count(), input_names: None, output_count: NUM_OUTPUTS, output_names: None })
+    }
+
+    fn test_era_info() -> EraInfo {
+        EraInfo { generations: 0, eras: 0, is_era_boundary: false, is_end_special_fitness: false, fitness_kind: EraFitness::Normal }
+    }
+
+    /// A path under the temp dir that's unique to this test process; callers remove it before asserting.
+    fn test_temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("snake-bevy-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_games_per_net() {
+        let mut my_meta = NnPlaysSnake::new().my_meta;
+        assert_eq!(Ok(()), my_meta.validate());
+        my_meta.games_per_net = GamesPerNetSchedule::Fixed(0);
+        let err = my_meta.validate().unwrap_err();
+        assert!(err.to_string().contains("games_per_net"), "{err}");
+    }
+
+    #[test]
+    fn test_looping_game_stops_only_at_cap_without_bail() {
+        // A length-2 snake can step back onto its tail forever, just like a net stuck in a loop
+        let mut game = SnakeGame::new(None);
+        let mut stopped_at = [None, None];
+        let mut moves = 0;
+        while stopped_at[0].is_none() {
+            let toward_tail = game.snake.locations[1] - game.snake.head_location;
+            let dir = Direction::from_index((0..4).find(|&i| Direction::from_index(i).to_point() == toward_tail).unwrap());
+            game.move_snake(dir, None);
+            assert_eq!(GameState::Running, game.state);
+            moves += 1;
+            for (stopped, bail_early) in stopped_at.iter_mut().zip([false, true]) {
+                if stopped.is_none() && NnPlaysSnake::should_stop_game(&game, moves, 0, bail_early) { *stopped = Some(moves); }
+            }
+        }
+        assert_eq!(Some(MAX_MOVES_PER_GAME), stopped_at[0]);
+        assert!(stopped_at[1].unwrap() < 1_000, "{stopped_at:?}");
+    }
+
+    #[test]
+    fn test_overflowing_weights_give_a_deterministic_move() {
+        use crate::neural_net::{activation_functions::ActivationFunction, layers::Layer, nets::{ConnectionSpec, NetSpec, NodeSpec}};
+        let net_params = test_net().net_params;
+        let input_count = net_params.input_count;
+        let input  = NodeSpec { activation_function: ActivationFunction::None,    layer: Layer::Input };
+        let output = NodeSpec { activation_function: ActivationFunction::Sigmoid, layer: Layer::Output };
+        // Every output gets 2 * MAX, which overflows to inf, minus the same; inf - inf is NaN.  But
+        // East also gets an ordinary input, to be enabled below.
+        let mut connections = (0..NUM_OUTPUTS).flat_map(|i| [
+            ConnectionSpec { from: 0, to: input_count + i, weight:  f32::MAX, is_enabled: true },
+            ConnectionSpec { from: 1, to: input_count + i, weight: -f32::MAX, is_enabled: true },
+        ]).collect::<Vec<_>>();
+        connections.push(ConnectionSpec { from: 2, to: input_count + 1, weight: 1.0, is_enabled: false });
+        let mut spec = NetSpec { net_params, nodes: [vec![input; input_count], vec![output; NUM_OUTPUTS]].concat(), connections };
+        let mut inputs = vec![0.0; input_count];
+        (inputs[0], inputs[1], inputs[2]) = (2.0, 2.0, 1.0);
+        let evaluated = |spec: &NetSpec| {
+            let mut net = Net::<MyFitnessInfo>::from_spec(spec).unwrap();
+            net.set_inputs(&inputs);
+            net.evaluate();
+            net
+        };
+
+        let net = evaluated(&spec);
+        assert!(net.get_outputs().iter().all(|v| v.is_nan()));
+        for output_policy in [OutputPolicy::Argmax, OutputPolicy::SoftmaxSample { temperature: 1.0 }] {
+            assert_eq!(Direction::North, NnPlaysSnake::interpret_outputs(&net, output_policy, &mut thread_rng()));
+        }
+
+        // A NaN among ordinary outputs is never chosen
+        spec.connections[2].is_enabled = false;
+        spec.connections[3].is_enabled = false;
+        spec.connections[2 * NUM_OUTPUTS].is_enabled = true;
+        let net = evaluated(&spec);
+        assert_eq!(Direction::East, NnPlaysSnake::interpret_outputs(&net, OutputPolicy::Argmax, &mut thread_rng()));
+    }
+
+    #[test]
+    fn test_fitness_aggregation() {
+        let games = [10.0, 40.0, 25.0].map(|fitness| MyFitnessInfo { fitness, apples: fitness / 10.0, ..Default::default() });
+        let min = FitnessAggregation::Min.aggregate(&games);
+        assert_eq!((10.0, 1.0), (min.fitness, min.apples));
+        assert_eq!(40.0, FitnessAggregation::Max.aggregate(&games).fitness);
+        assert_eq!(25.0, FitnessAggregation::Mean.aggregate(&games).fitness);
+        assert_eq!(36.25, FitnessAggregation::BlendMaxMean { w: 0.75 }.aggregate(&games).fitness);
+    }
+
+    #[test]
+    fn test_apple_histogram_buckets() {
+        let nets = [0.0, 2.0, 2.4, 1.6, 5.0, 0.2].map(|apples| {
+            let mut net = test_net();
+            net.fitness_info.apples = apples;
+            net
+        });
+        let histogram = NnPlaysSnake::apple_histogram(&nets);
+        assert_eq!(BTreeMap::from([(0, 2), (2, 3), (5, 1)]), histogram);
+    }
+
+    #[test]
+    fn test_softmax_temperature() {
+        let outputs = [0.2, 0.9, 0.5, 0.1];
+        let mut rng = thread_rng();
+        for _ in 0..1000 {
+            assert_eq!(1, NnPlaysSnake::softmax_sample(&outputs, 0.0,  &mut rng));
+            assert_eq!(1, NnPlaysSnake::softmax_sample(&outputs, 1e-3, &mut rng));
+        }
+        let mut counts = [0; 4];
+        for _ in 0..10_000 {
+            counts[NnPlaysSnake::softmax_sample(&outputs, 1e3, &mut rng)] += 1;
+        }
+        assert!(counts.iter().all(|&c| (2_000..3_000).contains(&c)), "counts={counts:?}");
+    }
+
+    #[test]
+    fn test_on_new_champion_fires_once() {
+        let count = Rc::new(Cell::new(0));
+        let count_in_callback = count.clone();
+        let mut on_new_champion: Option<ChampionCallback> = Some(Box::new(move |_, _, _, _, _| count_in_callback.set(count_in_callback.get() + 1)));
+        let mut global_max = MyFitnessInfo { fitness: 5.0, ..Default::default() };
+        let mut stashed_nets = Vec::new();
+        let playback = Playback { playback_events: Vec::new(), config: GameConfig::default() };
+        let net = test_net();
+
+        let better = MyFitnessInfo { fitness: 10.0, ..Default::default() };
+        let worse  = MyFitnessInfo { fitness:  7.0, ..Default::default() };
+        assert!( NnPlaysSnake::check_for_new_champion(&mut global_max, &mut stashed_nets, &mut on_new_champion, &net, &better, &[], &playback, 1));
+        assert!(!NnPlaysSnake::check_for_new_champion(&mut global_max, &mut stashed_nets, &mut on_new_champion, &net, &better, &[], &playback, 2));
+        assert!(!NnPlaysSnake::check_for_new_champion(&mut global_max, &mut stashed_nets, &mut on_new_champion, &net, &worse,  &[], &playback, 3));
+        assert_eq!(1, count.get());
+        assert_eq!(1, stashed_nets.len());
+        assert_eq!(10.0, global_max.fitness);
+    }
+
+    #[test]
+    fn test_short_run_summary() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.set_on_new_champion(None);
+        #[cfg(feature = "progress")]
+        nn_player.set_show_progress(false);
+        nn_player.my_meta.max_generations = 3;
+        nn_player.my_meta.games_per_net = GamesPerNetSchedule::Fixed(1);
+        nn_player.population.population_params.population_size = 20;
+        let summary = nn_player.run_x_generations().unwrap();
+        assert_eq!(3, summary.generations);
+        assert_eq!(nn_player.stashed_nets.len(), summary.champions);
+        assert_eq!(nn_player.max_info.fitness, summary.best.fitness);
+        assert_eq!((0, 0, 0), (summary.eras, summary.cataclysms, summary.resurrections));
+    }
+
+    #[test]
+    fn test_stop_request_writes_checkpoint() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.set_on_new_champion(None);
+        #[cfg(feature = "progress")]
+        nn_player.set_show_progress(false);
+        nn_player.my_meta.max_generations = 3;
+        nn_player.my_meta.games_per_net = GamesPerNetSchedule::Fixed(1);
+        nn_player.population.population_params.population_size = 20;
+        nn_player.population.create_initial_population();
+        let dir = test_temp_path("checkpoint");
+        nn_player.set_checkpoint_dir(dir.clone());
+        nn_player.stop_requested().store(true, Ordering::SeqCst);
+        let summary = nn_player.run_x_generations().unwrap();
+        let path = dir.join("Checkpoint-Gen000000.json");
+        let checkpoint = Checkpoint::from_file(path.to_str().unwrap());
+        assert_eq!(0, summary.generations);
+        assert_eq!(Some(path.clone()), summary.checkpoint);
+        let mut checkpoint = checkpoint.unwrap();
+        assert_eq!(0, checkpoint.generation);
+        assert_eq!(20, checkpoint.nets.len());
+        assert!(checkpoint.nets.iter().all(|n| n.id.0 < checkpoint.id_registry.net_id_next));
+
+        // Resuming hands out ids past the saved ones, as a fresh process would need
+        checkpoint.id_registry.net_id_next += 1_000;
+        checkpoint.id_registry.node_id_next += 1_000;
+        checkpoint.id_registry.connection_id_next += 1_000;
+        std::fs::write(&path, serde_json::to_string(&checkpoint).unwrap()).unwrap();
+        let mut resumed = NnPlaysSnake::new();
+        let loaded = resumed.resume_from_checkpoint(path.to_str().unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        loaded.unwrap();
+        assert_eq!(20, resumed.population.nets.len());
+        let after = IdRegistry::current();
+        assert!(after.net_id_next >= checkpoint.id_registry.net_id_next);
+        assert!(after.node_id_next >= checkpoint.id_registry.node_id_next);
+        assert!(after.connection_id_next >= checkpoint.id_registry.connection_id_next);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let new_player = || {
+            let mut nn_player = NnPlaysSnake::new();
+            nn_player.set_on_new_champion(None);
+            #[cfg(feature = "progress")]
+            nn_player.set_show_progress(false);
+            nn_player.my_meta.games_per_net = GamesPerNetSchedule::Fixed(1);
+            nn_player.population.population_params.population_size = 20;
+            nn_player
+        };
+        let dir = test_temp_path("resume");
+        let mut nn_player = new_player();
+        nn_player.set_checkpoint_dir(dir.clone());
+        nn_player.my_meta.max_generations = 2;
+        assert_eq!(2, nn_player.run_x_generations().unwrap().generations);
+        // Later runs continue from where the last one left off; this one stops right away
+        nn_player.my_meta.max_generations = 4;
+        nn_player.stop_requested().store(true, Ordering::SeqCst);
+        let path = nn_player.run_x_generations().unwrap().checkpoint.unwrap();
+        assert_eq!(dir.join("Checkpoint-Gen000002.json"), path);
+
+        let mut resumed = new_player();
+        let loaded = resumed.resume_from_checkpoint(path.to_str().unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        loaded.unwrap();
+        let ids = |nn_player: &NnPlaysSnake| nn_player.population.nets.iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(ids(&nn_player), ids(&resumed));
+        assert_eq!((2, nn_player.max_info.fitness), (resumed.next_generation, resumed.max_info.fitness));
+        resumed.my_meta.max_generations = 4;
+        assert_eq!(2, resumed.run_x_generations().unwrap().generations);
+        assert_eq!(4, resumed.next_generation);
+        assert!(resumed.resume_from_checkpoint("no-such-Checkpoint.json").is_err());
+    }
+
+    #[test]
+    fn test_games_per_net_schedule() {
+        let schedule = GamesPerNetSchedule::Linear { start: 1, end: 5, generations: 1_000 };
+        assert_eq!(1, schedule.games_at(0));
+        assert_eq!(3, schedule.games_at(500));
+        assert_eq!(5, schedule.games_at(1_000));
+        assert_eq!(5, schedule.games_at(50_000));
+        assert_eq!(2, GamesPerNetSchedule::Fixed(2).games_at(0));
+        assert_eq!(2, GamesPerNetSchedule::Fixed(2).games_at(50_000));
+    }
+
+    #[test]
+    fn test_direct_path_beats_meandering() {
+        let era_info = test_era_info();
+        let play = |dirs: &[Direction]| {
+            let mut game = SnakeGame::from_playback(&[
+                PlaybackEvents::NewGame,
+                PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+                PlaybackEvents::NewAppleLocation(GridPoint::new(8, 5)),
+            ]);
+            let mut detour_tracker = DetourTracker::new(&game);
+            for &dir in dirs {
+                let apples_before = game.apples_eaten;
+                game.move_snake(dir, Some(GridPoint::new(20, 20)));
+                let has_eaten = apples_before != game.apples_eaten;
+                detour_tracker.after_move(&game, has_eaten);
+                if has_eaten { game.clear_visited(); }
+            }
+            NnPlaysSnake::fitness_info_of_game(&era_info, &game, dirs.len(), &detour_tracker, None, 0.0)
+        };
+        use Direction::*;
+        let direct     = play(&[East, East, East]);
+        let meandering = play(&[North, East, East, East, South]);
+        assert_eq!((1.0, 0.0), (direct.apples, direct.detour));
+        assert_eq!((1.0, 2.0), (meandering.apples, meandering.detour));
+        assert!(direct.fitness > meandering.fitness, "direct={direct}, meandering={meandering}");
+    }
+
+    #[test]
+    fn test_trap_penalty_only_when_boxed_in() {
+        let era_info = test_era_info();
+        // Circles the 2x2 corner 10 times; walled in, only the two cells behind the head are free
+        let fitness_of = |walled_in: bool, trap_penalty: Option<TrapPenalty>| {
+            let obstacles = if walled_in { vec![GridPoint::new(3, 1), GridPoint::new(3, 2), GridPoint::new(1, 3), GridPoint::new(2, 3)] } else { vec![] };
+            let level = Level { width: Grid::WIDTH, height: Grid::HEIGHT, obstacles, snake: vec![GridPoint::new(2, 2), GridPoint::new(2, 1)], apple: GridPoint::new(30, 25) };
+            let mut game = SnakeGame::from_level(&level).unwrap();
+            let mut my_meta = NnPlaysSnake::new().my_meta;
+            my_meta.trap_penalty = trap_penalty;
+            use Direction::*;
+            let mut moves = [West, South, East, North].into_iter().cycle().take(40);
+            let info = NnPlaysSnake::play_and_score(&mut game, &era_info, &my_meta, |_| moves.next().map(|dir| (dir, None)));
+            assert_eq!(GameState::Running, game.state);
+            info.fitness
+        };
+        let trap_penalty = Some(TrapPenalty { min_free_space: 10, grace_moves: 8, per_move: 5.0 });
+        assert_eq!(fitness_of(false, None), fitness_of(false, trap_penalty));
+        assert!(((fitness_of(true, None) - fitness_of(true, trap_penalty)) - 5.0 * 32.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_drop_out_inputs_zeroes_about_the_rate() {
+        let mut rng = StdRng::seed_from_u64(1709);
+        for rate in [0.0, 0.1, 0.5] {
+            let mut inputs = vec![1.0_f32; 10_000];
+            NnPlaysSnake::drop_out_inputs(&mut inputs, rate, &mut rng);
+            let dropped = inputs.iter().filter(|&&v| v == 0.0).count() as f64 / inputs.len() as f64;
+            assert!((dropped - rate).abs() < 0.02, "rate={rate}, dropped={dropped}");
+            assert!(inputs.iter().all(|&v| v == 0.0 || v == 1.0));
+        }
+    }
+
+    #[test]
+    fn test_path_efficiency_rewards_not_revisiting() {
+        let era_info = EraInfo { generations: 0, eras: 0, is_era_boundary: false, is_end_special_fitness: false, fitness_kind: EraFitness::FavorVisits };
+        let play = |dirs: Vec<Direction>, efficiency_weight: f32| {
+            let mut game = SnakeGame::from_playback(&[
+                PlaybackEvents::NewGame,
+                PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+                PlaybackEvents::NewAppleLocation(GridPoint::new(30, 25)),
+            ]);
+            let mut my_meta = NnPlaysSnake::new().my_meta;
+            my_meta.efficiency_weights[EraFitness::FavorVisits as usize] = efficiency_weight;
+            let moves = dirs.len();
+            let mut dirs = dirs.into_iter();
+            let info = NnPlaysSnake::play_and_score(&mut game, &era_info, &my_meta, |_| dirs.next().map(|dir| (dir, None)));
+            assert_eq!(GameState::Running, game.state);
+            (NnPlaysSnake::path_efficiency(game.points_visited, moves), info.fitness)
+        };
+        use Direction::*;
+        let straight = || vec![East; 20];
+        // Round and round a 2x2 square, revisiting the same 4 cells
+        let pacing = || [North, West, South, East].into_iter().cycle().take(20).collect::<Vec<_>>();
+        assert_eq!(1.0, play(straight(), 0.0).0);
+        assert!((play(pacing(), 0.0).0 - 0.2).abs() < 1e-6);
+        let bonus = |dirs: fn() -> Vec<Direction>| play(dirs(), 100.0).1 - play(dirs(), 0.0).1;
+        assert!((bonus(straight) - 100.0).abs() < 1e-3);
+        assert!((bonus(pacing) - 20.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cataclism_keeps_minimum_population() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.population.population_params.population_size = 40;
+        nn_player.my_meta.min_population_after_cataclysm = 10;
+        nn_player.population.create_initial_population();
+        for net in nn_player.population.nets.iter_mut() {
+            net.fitness_info = MyFitnessInfo { fitness: 1.0, apples: 0.0, visited: 0.0, ..Default::default() };
+        }
+        // With uniform fitness, nothing is above the benchmark (whichever is picked), so everyone would be removed
+        nn_player.event_cataclism_remove_fewest_apples();
+        assert_eq!(10, nn_player.population.nets.len());
+        nn_player.event_cataclism_remove_fewest_visited();
+        assert_eq!(10, nn_player.population.nets.len());
+
+        // A population already below the minimum gets topped up
+        nn_player.population.nets.truncate(3);
+        nn_player.event_cataclism_remove_fewest_apples();
+        assert_eq!(10, nn_player.population.nets.len());
+    }
+
+    #[test]
+    fn test_jittered_resurrection() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.population.population_params.population_size = 20;
+        nn_player.my_meta.meta.population_size = 20;
+        nn_player.population.population_params.mutation_params.prob_add_connection = 1.0;
+        nn_player.population.create_initial_population();
+        let mut champion = nn_player.population.nets[0].clone();
+        champion.fitness_info.fitness = 100.0;
+        nn_player.stashed_nets.push(StashInfo { net: champion.clone(), generation: 1 });
+
+        nn_player.event_resurrect_jittered(0.5);
+        assert_eq!(20, nn_player.population.nets.len());
+        let resurrected = nn_player.population.nets.last().unwrap();
+        assert_ne!(champion.id, resurrected.id);
+        let (ids, weights) = champion.to_adjacency_matrix();
+        let (ids_resurrected, weights_resurrected) = resurrected.to_adjacency_matrix();
+        assert_eq!(ids, ids_resurrected);
+        assert_ne!(weights, weights_resurrected);
+        assert_eq!(f32::MIN, resurrected.fitness_info.fitness);
+    }
+
+    #[test]
+    fn test_mirrored_board_plays_the_same_game() {
+        // A simple deterministic policy standing in for a net: head for the apple, along x first.
+        fn policy(inputs: &[f32]) -> Direction {
+            match (inputs[8], inputs[9]) {
+                (dx, _) if dx < 0.0 => Direction::East,
+                (dx, _) if dx > 0.0 => Direction::West,
+                (_, dy) if dy < 0.0 => Direction::North,
+                _                   => Direction::South,
+            }
+        }
+        let mirror = |pt: GridPoint| GridPoint::new(Grid::WIDTH - 1 - pt.x, pt.y);
+        let apples = [GridPoint::new(10, 5), GridPoint::new(3, 20), GridPoint::new(30, 12), GridPoint::new(6, 6)];
+        let play = |transform: BoardTransform, to_board: &dyn Fn(GridPoint) -> GridPoint| {
+            let mut game = SnakeGame::from_playback(&[
+                PlaybackEvents::NewGame,
+                PlaybackEvents::NewSnakeLocation(to_board(GridPoint::new(20, 15)), to_board(GridPoint::new(20, 14))),
+                PlaybackEvents::NewAppleLocation(to_board(GridPoint::new(25, 15))),
+            ]);
+            game.apple_policy = ApplePolicy::Sequence(apples.iter().map(|&pt| to_board(pt)).collect());
+            let mut heads = Vec::new();
+            for _ in 0..150 {
+                if game.state != GameState::Running { break; }
+                let dir = transform.to_board_direction(policy(&InputEncoder::Standard.encode(&game, transform, InputEncoder::DEFAULT_RANGE, None)));
+                game.move_snake(dir, None);
+                heads.push(game.snake.head_location);
+            }
+            (game.apples_eaten, game.state, heads)
+        };
+        let (apples_real, state_real, heads_real) = play(BoardTransform::Identity, &|pt| pt);
+        let (apples_mirror, state_mirror, heads_mirror) = play(BoardTransform::MirrorX, &mirror);
+        assert!(apples_real > 0);
+        assert_eq!((apples_real, state_real), (apples_mirror, state_mirror));
+        assert_eq!(heads_real, heads_mirror.into_iter().map(mirror).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_apple_value_decays_with_moves() {
+        let era_info = test_era_info();
+        let fitness_of = |apple_moves: Vec<usize>, apple_value_decay: Option<f32>| {
+            let game = SnakeGame::new(None);
+            let moves = apple_moves.iter().sum();
+            let detour_tracker = DetourTracker { optimal_moves: 0, moves: 0, detour: 0, apple_moves };
+            NnPlaysSnake::fitness_info_of_game(&era_info, &game, moves, &detour_tracker, apple_value_decay, 0.0).fitness
+        };
+        let decay = Some(0.99);
+        assert!(fitness_of(vec![10, 20], decay) > fitness_of(vec![30, 40], decay));
+        assert!(fitness_of(vec![10, 20], decay) > fitness_of(vec![10, 21], decay));
+        // Without decay, only the (unchanged) small moves penalty differs
+        assert!((fitness_of(vec![10, 20], None) - fitness_of(vec![30, 40], None)).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_longer_snake_scores_higher_with_length_weight() {
+        let era_info = test_era_info();
+        // Both eat the same apple, but only the long one lives to finish growing
+        let fitness_of = |moves: usize, length_weight: f32| {
+            let mut game = SnakeGame::from_playback(&[
+                PlaybackEvents::NewGame,
+                PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+                PlaybackEvents::NewAppleLocation(GridPoint::new(6, 5)),
+            ]);
+            for _ in 0..moves { game.move_snake(Direction::East, Some(GridPoint::new(20, 20))); }
+            let detour_tracker = DetourTracker::new(&game);
+            let info = NnPlaysSnake::fitness_info_of_game(&era_info, &game, moves, &detour_tracker, None, length_weight);
+            (info.apples, game.snake.length(), info.fitness)
+        };
+        let (short, long) = (fitness_of(2, 100.0), fitness_of(6, 100.0));
+        assert_eq!((1.0, 3), (short.0, short.1));
+        assert_eq!((1.0, 7), (long.0, long.1));
+        assert!(long.2 > short.2, "short={short:?}, long={long:?}");
+        let gain = |moves| fitness_of(moves, 100.0).2 - fitness_of(moves, 0.0).2;
+        assert!((gain(2) - 300.0).abs() < 1e-3 && (gain(6) - 700.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_dump_fitness_has_one_row_per_net() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.population.population_params.population_size = 20;
+        nn_player.population.create_initial_population();
+        let path = test_temp_path("Fitness").join("Fitness-Gen000000.csv");
+        NnPlaysSnake::dump_population_fitness(&nn_player.population.nets, &path).unwrap();
+        let s = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let mut lines = s.lines();
+        assert_eq!(Some("id,fitness,apples,visited,moves,nodes,connections"), lines.next());
+        let rows = lines.map(|line| line.split(',').collect::<Vec<_>>()).collect::<Vec<_>>();
+        assert_eq!(20, rows.len());
+        assert!(rows.iter().all(|row| row.len() == 7));
+        assert_eq!(nn_player.population.nets[3].id.0.to_string(), rows[3][0]);
+    }
+
+    #[test]
+    fn test_metrics_jsonl_parses_back() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.population.population_params.population_size = 20;
+        nn_player.set_on_new_champion(None);
+        let path = test_temp_path("Metrics").join("metrics.jsonl");
+        nn_player.set_metrics_jsonl(Some(&path)).unwrap();
+        let era_info = test_era_info();
+        nn_player.run_one_generation(0, &era_info, 1);
+        nn_player.events_fired.push("resurrection");
+        let era_info = EraInfo { eras: 1, is_era_boundary: true, fitness_kind: EraFitness::FavorVisits, ..era_info };
+        nn_player.run_one_generation(1, &era_info, 1);
+        nn_player.set_metrics_jsonl(None).unwrap();
+
+        let s = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        let stats = s.lines().map(|line| serde_json::from_str::<GenerationStats>(line).unwrap()).collect::<Vec<_>>();
+        assert_eq!(vec![0, 1], stats.iter().map(|st| st.generation).collect::<Vec<_>>());
+        assert!(stats[0].events.is_empty());
+        assert_eq!(vec!["resurrection".to_string()], stats[1].events);
+        assert_eq!((EraFitness::FavorVisits, 1, true), (stats[1].fitness_kind, stats[1].eras, stats[1].is_era_boundary));
+        assert!(stats.iter().all(|st| st.best.fitness >= st.mean.fitness && st.best.moves > 0.0));
+    }
+
+    #[test]
+    fn test_era_mutation_params_override() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.my_meta.era_mutation_params[EraFitness::FavorVisits as usize] = Some(MutationParams::weights_only());
+        assert_eq!(MutationParams::weights_only(), *nn_player.mutation_params_for(EraFitness::FavorVisits));
+        assert_eq!(MutationParams::neat_default(), *nn_player.mutation_params_for(EraFitness::Normal));
+        assert_eq!(Ok(()), nn_player.my_meta.validate());
+        nn_player.my_meta.era_mutation_params[EraFitness::FavorMoves as usize] = Some(MutationParams { prob_add_node: 2.0, ..MutationParams::aggressive() });
+        assert!(nn_player.my_meta.validate().unwrap_err().0.starts_with("era_mutation_params[2]: prob_add_node"));
+
+        // Breeding in the era uses its params, then goes back to the usual ones
+        nn_player.my_meta.era_mutation_params[EraFitness::FavorMoves as usize] = None;
+        nn_player.population.population_params.population_size = 20;
+        let era_info = EraInfo { generations: 0, eras: 0, is_era_boundary: false, is_end_special_fitness: false, fitness_kind: EraFitness::FavorVisits };
+        nn_player.set_on_new_champion(None);
+        nn_player.run_one_generation(0, &era_info, 1);
+        assert_eq!(MutationParams::neat_default(), nn_player.population.population_params.mutation_params);
+    }
+
+    #[test]
+    fn test_seeded_boards_are_the_same_within_a_generation() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.my_meta.seeded_boards = true;
+        nn_player.population.population_params.population_size = 20;
+        nn_player.population.create_initial_population();
+        let mut net = nn_player.population.nets[0].clone();
+        net.build_evaluation_order();
+        let era_info = test_era_info();
+        let mut play = |generation| {
+            let mut game = SnakeGame::new(None);
+            let info = NnPlaysSnake::play_games(&mut net, &mut game, &era_info, &nn_player.my_meta, generation, 3, &mut Vec::new());
+            (info.fitness, info.apples, info.moves, game.playback.playback_events)
+        };
+        let first = play(7);
+        assert_eq!(first, play(7));
+        assert_ne!(NnPlaysSnake::board_seed(7, 0), NnPlaysSnake::board_seed(8, 0));
+        assert_ne!(NnPlaysSnake::board_seed(7, 0), NnPlaysSnake::board_seed(7, 1));
+    }
+
+    #[test]
+    fn test_identical_nets_share_a_fingerprint() {
+        let mut nn_player = NnPlaysSnake::new();
+        nn_player.population.population_params.population_size = 20;
+        nn_player.population.create_initial_population();
+        let my_meta = &nn_player.my_meta;
+        let mut net = nn_player.population.nets[0].clone();
+        let mut copy = net.clone_as_new_net();
+        let fingerprint = NnPlaysSnake::behavior_fingerprint(&mut net, my_meta);
+        assert_ne!(0, fingerprint);
+        assert_eq!(fingerprint, NnPlaysSnake::behavior_fingerprint(&mut copy, my_meta));
+        assert_eq!(fingerprint, NnPlaysSnake::behavior_fingerprint(&mut net, my_meta));
+
+        nn_player.population.nets.truncate(3);
+        let clone = nn_player.population.nets[0].clone();
+        nn_player.population.nets.extend([clone.clone(), clone]);
+        assert!(nn_player.distinct_behavior_count() <= 3);
+    }
+
+    #[test]
+    fn test_robustness_report() {
+        let nn_player = NnPlaysSnake::new();
+        let mut net = test_net();
+        net.build_evaluation_order();
+        let seeds = [1, 2, 3, 4, 5, 6];
+        let report = nn_player.evaluate_robustness(&net, &seeds);
+        assert_eq!(6, report.games);
+        assert!(report.min_apples as f32 <= report.mean_apples && report.mean_apples <= report.max_apples as f32, "{report:?}");
+        assert!(report.std_apples >= 0.0);
+        assert_eq!(1.0, report.died + report.move_limited);
+        // Seeded boards make it repeatable
+        assert_eq!(report, nn_player.evaluate_robustness(&net, &seeds));
+    }
+
+    #[test]
+    fn test_champion_record_round_trips_and_replays() {
+        // Several games, each through two transforms, aggregated as in training
+        let mut my_meta = NnPlaysSnake::new().my_meta;
+        my_meta.board_transforms = vec![BoardTransform::Identity, BoardTransform::MirrorX];
+        let my_meta = &my_meta;
+        let era_info = test_era_info();
+        let mut net = Net::random_with(test_net().net_params, &MutationParams::neat_default(), &mut StdRng::seed_from_u64(1667));
+        net.build_evaluation_order();
+        let mut game = SnakeGame::new(None);
+        let mut earlier_games = Vec::new();
+        let fitness_info = NnPlaysSnake::play_games(&mut net, &mut game, &era_info, my_meta, 1, 3, &mut earlier_games);
+        assert_eq!(5, earlier_games.len());
+        let record = ChampionRecord { net, playback: game.playback.clone(), earlier_games, generation: 7, fitness_info };
+
+        let path = test_temp_path("Champion.json");
+        std::fs::write(&path, serde_json::to_string(&record).unwrap()).unwrap();
+        let loaded = ChampionRecord::from_file(path.to_str().unwrap());
+        let playback = Playback::from_file(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(game.playback.playback_events, playback.unwrap().playback_events);
+        assert_eq!(game.playback.playback_events, loaded.playback.playback_events);
+        assert_eq!((record.net.id, 7), (loaded.net.id, loaded.generation));
+        assert_eq!(record.earlier_games[0].playback_events, loaded.earlier_games[0].playback_events);
+
+        let fields = |info: &MyFitnessInfo| (info.fitness, info.apples, info.visited, info.moves, info.coverage, info.detour);
+        assert_eq!(fields(&fitness_info), fields(&loaded.fitness_info));
+        assert_eq!(fields(&fitness_info), fields(&loaded.replay_fitness_info(&era_info, my_meta)));
+    }
+
+    #[test]
+    fn test_seeded_champion_replays_with_its_initial_growth() {
+        let mut my_meta = NnPlaysSnake::new().my_meta;
+        my_meta.initial_to_grow = 5;
+        my_meta.starvation_limit = Some(60);
+        let era_info = test_era_info();
+        let mut net = test_net();
+        net.build_evaluation_order();
+        my_meta.seeded_boards = true;
+        let mut game = SnakeGame::new(None);
+        let mut earlier_games = Vec::new();
+        let fitness_info = NnPlaysSnake::play_games(&mut net, &mut game, &era_info, &my_meta, 0, 2, &mut earlier_games);
+        assert_eq!(5, game.playback.config.initial_to_grow);
+        assert_eq!(Some(60), game.playback.config.starvation_limit);
+
+        let record = ChampionRecord { net, playback: game.playback.clone(), earlier_games, generation: 0, fitness_info };
+        let fields = |info: &MyFitnessInfo| (info.fitness, info.apples, info.visited, info.moves, info.coverage, info.detour);
+        assert_eq!(fields(&fitness_info), fields(&record.replay_fitness_info(&era_info, &my_meta)));
+    }
+
+    #[test]
+    fn test_seeded_game_is_reproducible() {
+        let mut my_meta = NnPlaysSnake::new().my_meta;
+        my_meta.input_dropout = Some(0.3);
+        my_meta.output_policy = OutputPolicy::SoftmaxSample { temperature: 1.0 };
+        let era_info = test_era_info();
+        let mut net = Net::random_with(test_net().net_params, &MutationParams::neat_default(), &mut StdRng::seed_from_u64(1698));
+        net.build_evaluation_order();
+        let mut play = || {
+            let mut game = SnakeGame::new(None);
+            let info = NnPlaysSnake::run_one_seeded_game(&mut net, &mut game, &era_info, &my_meta, BoardTransform::Identity, 42);
+            (info.fitness, info.apples, info.moves, game.playback.playback_events)
+        };
+        let first = play();
+        assert_eq!(first, play());
+    }
+
+    #[test]
+    fn test_load_net_without_newer_fitness_fields() {
+        let mut net_json = serde_json::to_value(test_net()).unwrap();
+        let fitness_info = net_json["fitness_info"].as_object_mut().unwrap();
+        for field in ["coverage", "detour"] {
+            assert!(fitness_info.remove(field).is_some(), "{field}");
+        }
+        let path = test_temp_path("OldNet.json");
+        std::fs::write(&path, net_json.to_string()).unwrap();
+        let net = NnPlaysSnake::load_net(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let fitness_info = net.unwrap().fitness_info;
+        assert_eq!((0.0, 0.0), (fitness_info.coverage, fitness_info.detour));
+    }
+
+    #[test]
+    fn test_bench_net() {
+        let path = test_temp_path("BenchNet.json");
+        std::fs::write(&path, serde_json::to_string(&test_net()).unwrap()).unwrap();
+        let report = NnPlaysSnake::bench_net(path.to_str().unwrap(), 10);
+        std::fs::remove_file(&path).unwrap();
+        assert!(report.unwrap().contains("10 evaluations"));
+        assert!(NnPlaysSnake::bench_net("no-such-Net.json", 10).is_err());
+    }
+
+    #[test]
+    fn test_trace_has_one_step_per_move() {
+        let era_info = test_era_info();
+        let my_meta = &NnPlaysSnake::new().my_meta;
+        let mut net = test_net();
+        net.build_evaluation_order();
+        let mut game = SnakeGame::new(None);
+        let fitness_info = NnPlaysSnake::run_one_game(&mut net, &mut game, &era_info, my_meta, BoardTransform::Identity);
+        let trace = NnPlaysSnake::trace_playback(&net, &game.playback, my_meta);
+        assert_eq!(fitness_info.moves as usize, trace.len());
+        assert_eq!(game.playback.move_count(), trace.len());
+        let directions = recorded_moves(&game.playback).map(|(dir, _)| dir).collect::<Vec<_>>();
+        assert_eq!(directions, trace.iter().map(|step| step.direction).collect::<Vec<_>>());
+        // Argmax through Identity: the net's outputs pick the move it made
+        for step in &trace {
+            assert_eq!(net.get_outputs().len(), step.outputs.len());
+            assert_eq!(NnPlaysSnake::output_direction(NnPlaysSnake::argmax(&step.outputs)), step.direction);
+        }
+    }
+
+    #[test]
+    fn test_inspect_net_shows_every_output() {
+        let nn_player = NnPlaysSnake::new();
+        let net = Net::<MyFitnessInfo>::new(nn_player.my_meta.meta.net_params.clone());
+        let path = test_temp_path("Net.json");
+        std::fs::write(&path, serde_json::to_string(&net).unwrap()).unwrap();
+        let report = NnPlaysSnake::inspect_net(path.to_str().unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+        let report = report.unwrap();
+        let outputs = report.lines().skip_while(|&line| line != "Outputs:").skip(1).take_while(|line| line.starts_with("  ")).collect::<Vec<_>>();
+        assert_eq!(NUM_OUTPUTS, outputs.len());
+        assert!(outputs[0].starts_with("  MoveN"));
+        assert!(report.contains("Chosen move: "));
+    }
+}
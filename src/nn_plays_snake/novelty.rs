@@ -0,0 +1,64 @@
+//! Novelty search: scoring a Net by how unlike earlier Nets' play its own play is, rather than by
+//! how well it played, so training keeps exploring when fitness alone has stalled.
+
+use crate::snake_game::{Grid, SnakeGame};
+
+/// What a game's play amounted to, for comparing with other games: where the head ended up, the
+/// apples eaten, and how much of the board was covered, each roughly in [0, 1].
+pub fn behavior_of_game(game: &SnakeGame) -> Vec<f32> {
+    vec![
+        game.snake.head_location.x as f32 / Grid::WIDTH  as f32,
+        game.snake.head_location.y as f32 / Grid::HEIGHT as f32,
+        game.apples_eaten as f32 / 10.0,    // Typical max is 9
+        game.coverage(),
+    ]
+}
+
+/// The behaviors seen so far, which new behaviors are measured against.
+#[derive(Clone, Debug, Default)]
+pub struct NoveltyArchive {
+    behaviors: Vec<Vec<f32>>,
+}
+
+impl NoveltyArchive {
+    pub fn len(&self) -> usize { self.behaviors.len() }
+
+    pub fn is_empty(&self) -> bool { self.behaviors.is_empty() }
+
+    pub fn add(&mut self, behavior: Vec<f32>) {
+        self.behaviors.push(behavior);
+    }
+
+    /// The mean distance from `behavior` to its `k` nearest behaviors in the archive, or 0.0 if
+    /// the archive is empty.
+    pub fn novelty(&self, behavior: &[f32], k: usize) -> f32 {
+        let mut distances = self.behaviors.iter().map(|other| distance(behavior, other)).collect::<Vec<_>>();
+        let k = k.min(distances.len());
+        if k == 0 { return 0.0; }
+        distances.sort_by(f32::total_cmp);
+        distances[..k].iter().sum::<f32>() / k as f32
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_far_behavior_is_more_novel() {
+        let mut archive = NoveltyArchive::default();
+        assert_eq!(0.0, archive.novelty(&[0.5, 0.5, 0.0, 0.1], 3));
+        archive.add(vec![0.5, 0.5, 0.0, 0.1]);
+        archive.add(vec![0.6, 0.5, 0.0, 0.1]);
+        archive.add(vec![0.5, 0.4, 0.1, 0.1]);
+        let near = archive.novelty(&[0.55, 0.5, 0.0, 0.1], 2);
+        let far  = archive.novelty(&[0.1, 0.9, 0.5, 0.6], 2);
+        assert!(far > near, "far={far}, near={near}");
+        assert_eq!(0.0, archive.novelty(&[0.5, 0.5, 0.0, 0.1], 1));
+    }
+}
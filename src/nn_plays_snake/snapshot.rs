@@ -0,0 +1,60 @@
+//! Small PNG pictures of a game's board, for eyeballing a run's progress without the viewer.
+
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+
+use crate::snake_game::{CellKind, Grid, GridPoint};
+
+/// Pixels per side of each cell in a snapshot.
+pub const CELL_PIXELS: u32 = 4;
+
+fn color_of(kind: CellKind) -> Rgba<u8> {
+    match kind {
+        CellKind::Empty => Rgba([ 16,  16,  16, 255]),
+        CellKind::Apple => Rgba([220,  40,  40, 255]),
+        CellKind::Snake => Rgba([ 60, 200,  60, 255]),
+        CellKind::Wall  => Rgba([128, 128, 128, 255]),
+        CellKind::Crash => Rgba([255, 220,   0, 255]),
+    }
+}
+
+/// Draws each cell of `grid` as a `cell_pixels`-square block.  Row 0 of the grid is at the bottom
+/// of the image, as on screen.
+pub fn grid_to_rgba(grid: &Grid, cell_pixels: u32) -> RgbaImage {
+    let (width, height) = (Grid::WIDTH as u32, Grid::HEIGHT as u32);
+    RgbaImage::from_fn(width * cell_pixels, height * cell_pixels, |px, py| {
+        let x = (px / cell_pixels) as i16;
+        let y = (height - 1 - py / cell_pixels) as i16;
+        color_of(grid.get_cell(GridPoint { x, y }).kind)
+    })
+}
+
+/// Writes `grid` as a PNG to `path`.
+pub fn write_grid_png(grid: &Grid, path: &Path) -> Result<(), String> {
+    grid_to_rgba(grid, CELL_PIXELS).save(path).map_err(|e| format!("writing {}: {e}", path.display()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snake_game::{Direction, GameState, SnakeGame};
+
+    #[test]
+    fn test_finished_game_writes_png() {
+        let mut game = SnakeGame::new(None);
+        while game.state == GameState::Running {
+            game.move_snake(Direction::North, None);
+        }
+        let image = grid_to_rgba(&game.grid, CELL_PIXELS);
+        assert_eq!((160, 120), image.dimensions());
+        assert_eq!(color_of(CellKind::Wall), *image.get_pixel(0, 0));
+
+        let path = std::env::temp_dir().join(format!("snake-bevy-test-{}-Snapshot.png", std::process::id()));
+        write_grid_png(&game.grid, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG"), "{} bytes", bytes.len());
+    }
+}
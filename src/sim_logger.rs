@@ -0,0 +1,102 @@
+//! A minimal `log` logger for `train` runs, which don't have Bevy's LogPlugin to print for them.
+//! Training output comes from `nn_plays_snake`, through `sim_info!` and `sim_debug!`: champions,
+//! eras, and cataclysms at `info`, per-generation stats at `debug`.  Everything else (e.g.
+//! `neural_net`) is only shown when `--verbose`.
+
+use std::fmt;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+const SIM_TARGET: &str = concat!(env!("CARGO_CRATE_NAME"), "::nn_plays_snake");
+
+struct SimLogger {
+    level_sim:   LevelFilter,
+    level_other: LevelFilter,
+}
+
+impl SimLogger {
+    fn new(verbosity: Verbosity) -> Self {
+        let (level_sim, level_other) = match verbosity {
+            Verbosity::Quiet   => (LevelFilter::Info,  LevelFilter::Warn),
+            Verbosity::Normal  => (LevelFilter::Debug, LevelFilter::Warn),
+            Verbosity::Verbose => (LevelFilter::Trace, LevelFilter::Trace),
+        };
+        Self { level_sim, level_other }
+    }
+}
+
+impl Log for SimLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = if metadata.target().starts_with(SIM_TARGET) { self.level_sim } else { self.level_other };
+        metadata.level() <= level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return; }
+        match record.level() {
+            log::Level::Error | log::Level::Warn => eprintln!("{}", record.args()),
+            _ => println!("{}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init(verbosity: Verbosity) {
+    let logger = SimLogger::new(verbosity);
+    log::set_max_level(logger.level_sim.max(logger.level_other));
+    log::set_logger(Box::leak(Box::new(logger))).expect("A logger was already set");
+}
+
+/// Whether training output at `level` is shown, e.g. to skip computing what it would report.
+pub fn is_enabled(level: Level) -> bool {
+    level <= log::max_level() && log::logger().enabled(&Metadata::builder().level(level).target(SIM_TARGET).build())
+}
+
+/// Logs training output at `level`.  Unlike `log`'s macros, this isn't compiled out of release
+/// builds (which keep only `warn` and above), so the `Verbosity` alone decides what's shown.
+pub fn log_sim(level: Level, args: fmt::Arguments) {
+    log::logger().log(&Record::builder().level(level).target(SIM_TARGET).args(args).build());
+}
+
+/// Like `log::info!`, for training output (see `log_sim()`).
+macro_rules! sim_info {
+    ($($arg:tt)+) => {
+        if $crate::sim_logger::is_enabled(log::Level::Info) { $crate::sim_logger::log_sim(log::Level::Info, format_args!($($arg)+)); }
+    };
+}
+pub(crate) use sim_info;
+
+/// Like `log::debug!`, for training output (see `log_sim()`).
+macro_rules! sim_debug {
+    ($($arg:tt)+) => {
+        if $crate::sim_logger::is_enabled(log::Level::Debug) { $crate::sim_logger::log_sim(log::Level::Debug, format_args!($($arg)+)); }
+    };
+}
+pub(crate) use sim_debug;
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_shown(verbosity: Verbosity, level: Level, target: &str) -> bool {
+        SimLogger::new(verbosity).enabled(&Metadata::builder().level(level).target(target).build())
+    }
+
+    #[test]
+    fn test_quiet_keeps_champions_but_not_generation_stats() {
+        assert!( is_shown(Verbosity::Quiet,  Level::Info,  SIM_TARGET));
+        assert!(!is_shown(Verbosity::Quiet,  Level::Debug, SIM_TARGET));
+        assert!( is_shown(Verbosity::Normal, Level::Debug, SIM_TARGET));
+        assert!(!is_shown(Verbosity::Normal, Level::Debug, "snake_bevy::neural_net::nets"));
+        assert!( is_shown(Verbosity::Verbose, Level::Debug, "snake_bevy::neural_net::nets"));
+    }
+}
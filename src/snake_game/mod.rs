@@ -1,7 +1,7 @@
 // TODO: Move into separate crate!
 
-use std::{collections::VecDeque, ops};
-use rand::Rng;
+use std::{collections::VecDeque, fmt, ops};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -15,6 +15,8 @@ impl Grid {
     pub const WIDTH:  i16 = 40;
     pub const HEIGHT: i16 = 30;
     pub const _MAX_WIDTH_HEIGHT: i16 = 25; // Maximum of width & height, i.e. WIDTH.max(HEIGHT)
+    /// Number of cells the snake could ever occupy, i.e. everything inside the perimeter wall.
+    pub const FREE_CELL_COUNT: usize = (Self::WIDTH as usize - 2) * (Self::HEIGHT as usize - 2);
 
     #[allow(clippy::new_without_default)]
     pub fn new() -> Grid {
@@ -51,6 +53,10 @@ impl Grid {
         let i = y * Self::WIDTH + x;
         cells[i as usize] = Cell { kind };
     }
+    /// How many cells are of `kind`, e.g. `Empty` ones left for the snake (or an apple).
+    pub fn count_kind(&self, kind: CellKind) -> usize {
+        self.cells.iter().filter(|cell| cell.kind == kind).count()
+    }
     pub fn is_in_bounds(&self, pt: GridPoint) -> bool {
         pt.x >= 0 && pt.y >= 0 && pt.x < Self::WIDTH && pt.y < Self::HEIGHT
     }
@@ -71,9 +77,20 @@ impl Grid {
         }
     }
     pub fn new_viable_apple_location(&self) -> GridPoint {
+        self.new_viable_apple_location_where(|_| true)
+    }
+    /// Like `new_viable_apple_location()`, but only where `allowed` (e.g. not right in front of the
+    /// snake).  If no empty cell is allowed, `allowed` is ignored.
+    pub fn new_viable_apple_location_where(&self, allowed: impl Fn(GridPoint) -> bool) -> GridPoint {
         for _ in 0..10000 {
             let loc = self.rand_point();
-            if self.get_cell(loc).kind != CellKind::Empty { continue; }
+            if self.get_cell(loc).kind != CellKind::Empty || !allowed(loc) { continue; }
+            return loc;
+        }
+        let empty_cells = (0..Self::HEIGHT).flat_map(|y| (0..Self::WIDTH).map(move |x| GridPoint::new(x, y)))
+            .filter(|&pt| self.get_cell(pt).kind == CellKind::Empty)
+            .collect::<Vec<_>>();
+        if let Some(&loc) = empty_cells.iter().find(|&&pt| allowed(pt)).or(empty_cells.first()) {
             return loc;
         }
         // TODO: In a good game, we might trigger this, so once snake is too long, we should
@@ -82,6 +99,16 @@ impl Grid {
         // through the grid, the user shouldn't notice!
         panic!("No room for apple!");
     }
+    /// `pt` if it's empty, otherwise the next empty cell after it (in row order, wrapping around).
+    pub fn viable_apple_location_at_or_after(&self, pt: GridPoint) -> GridPoint {
+        let cell_count = (Self::WIDTH * Self::HEIGHT) as usize;
+        let start = (pt.y * Self::WIDTH + pt.x) as usize;
+        for i in (0..cell_count).map(|offset| (start + offset) % cell_count) {
+            if self.cells[i].kind != CellKind::Empty { continue; }
+            return GridPoint { x: i as i16 % Self::WIDTH, y: i as i16 / Self::WIDTH };
+        }
+        panic!("No room for apple!");
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -121,6 +148,19 @@ impl Direction {
             _ => panic!("Bad i in from_index()"),
         }
     }
+    /// The direction of the single step from `from` to `to`, if they're adjacent.
+    pub fn between(from: GridPoint, to: GridPoint) -> Option<Direction> {
+        [Direction::North, Direction::East, Direction::South, Direction::West].into_iter()
+            .find(|dir| from + dir.to_point() == to)
+    }
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East  => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West  => Direction::East,
+        }
+    }
     pub fn to_point(self) -> GridPoint {
         match self {
             Direction::North => GridPoint { x: 0, y: 1, },
@@ -162,6 +202,11 @@ impl GridPoint {
     pub(crate) fn is_zero(&self) -> bool {
         self.x == 0 && self.y == 0
     }
+
+    /// Whether this is the offset of one step N, E, S, or W.
+    pub(crate) fn is_unit(&self) -> bool {
+        self.x.abs() + self.y.abs() == 1
+    }
 }
 
 impl ops::Add<Self> for GridPoint {
@@ -213,10 +258,57 @@ impl Snake {
         }
         panic!("No room for snake!");
     }
+    pub(self) fn restart_at(&mut self, grid: &mut Grid, head: GridPoint, tail: GridPoint) {
+        self.locations.clear();
+        self.to_grow = 0;
+        grid.get_cell_mut(head).kind = CellKind::Snake;
+        grid.get_cell_mut(tail).kind = CellKind::Snake;
+        self.locations.push_front(tail);
+        self.locations.push_front(head);
+        self.head_location = head;
+    }
 
     pub fn length(&self) -> usize {
         self.locations.len()
     }
+
+    /// The direction the head last moved, i.e. from the neck to the head.
+    pub fn heading(&self) -> Option<Direction> {
+        Direction::between(*self.locations.get(1)?, self.head_location)
+    }
+
+    /// Moves the tail (unless growing) and then the head one cell in `direction`, and returns what
+    /// the head ran into.  Eating and crashing are up to the caller.
+    ///
+    /// NOTE: Ordering matters here!  Because the old tail cell is emptied *before* we look at what
+    /// the head runs into, following your own tail is legal when not growing; but while growing
+    /// the tail stays put, so hitting it is a crash.
+    fn step(&mut self, grid: &mut Grid, direction: Direction) -> CellKind {
+        if self.to_grow == 0 {
+            // Snake keeps same size, so we must pop off the tail to keep the same length, we will shortly push on a new head.
+            let old_tail_location = self.locations.pop_back().unwrap();
+            grid.get_cell_mut(old_tail_location).kind = CellKind::Empty;
+        } else {
+            self.to_grow -= 1;
+        }
+        let new_location = self.head_location + direction.to_point();
+        let new_cell = grid.get_cell_mut(new_location);
+        let kind_hit = new_cell.kind;
+        new_cell.kind = CellKind::Snake;
+        self.locations.push_front(new_location);
+        self.head_location = new_location;
+        kind_hit
+    }
+}
+
+
+/// Another snake sharing the board (e.g. a bot opponent), which moves only via
+/// `SnakeGame::move_opponent()`.  Its body is as deadly as the player's own.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Opponent {
+    pub snake: Snake,
+    pub state: GameState,
+    pub apples_eaten: usize,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -224,6 +316,21 @@ pub struct Apple {
     pub location: GridPoint,
 }
 
+/// Where move_snake() puts the next apple after one is eaten (unless told explicitly, as in playback).
+/// Points that are occupied at the time give way to the next empty cell after them.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum ApplePolicy {
+    #[default]
+    RandomEachEat,
+    Fixed(GridPoint),
+    /// Hands out the points in order, wrapping around to the start when exhausted.
+    Sequence(Vec<GridPoint>),
+    /// Random, but never the cell right in front of the snake's head (a harder curriculum).
+    RandomNotAhead,
+    /// The cell right in front of the snake's head, when it's empty (a trivial curriculum).
+    Ahead,
+}
+
 
 
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -233,18 +340,266 @@ pub enum GameState {
 }
 
 
+/// Why a game ended.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum CrashReason {
+    Wall,
+    Snake,
+    /// Went more than `SnakeGame::starvation_limit` moves without eating.
+    Starved,
+    /// Made `SnakeGame::max_moves` moves.
+    MoveLimit,
+}
+
+
+/// What `move_snake()` does when told to reverse, i.e. to move back onto the snake's neck.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub enum ReversalRule {
+    /// Moves anyway, which crashes into the snake (as with any other move into it).
+    #[default]
+    Crash,
+    /// Treats it as continuing straight ahead.
+    Ignore,
+    /// Refuses the move: nothing happens, and nothing is recorded.
+    Forbidden,
+}
+
+
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum PlaybackEvents {
     NewGame,                        // Initialize grid
+    NewSnakeLocation(GridPoint, GridPoint), // Place snake (head, tail)
     NewAppleLocation(GridPoint),    // Place apple
     MoveSnake(Direction),           // Move snake
     GameOver,
 }
 
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Clone, Default, Serialize, Deserialize, Debug)]
 pub struct Playback {
     pub playback_events: Vec<PlaybackEvents>,
+    #[serde(default)]
+    pub config: GameConfig,     // As of the game's last restart(); older files have none, i.e. the defaults
+}
+
+/// What a playback file holds: a bare `Playback`, or a record (e.g. a champion's) embedding one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PlaybackFile {
+    Bare(Playback),
+    Embedded { playback: Playback },
+    Compact(CompactPlayback),
+}
+
+impl Playback {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        let playback = match serde_json::from_str(&s).map_err(|e| format!("parsing {path}: {e}"))? {
+            PlaybackFile::Bare(playback) | PlaybackFile::Embedded { playback } => playback,
+            PlaybackFile::Compact(compact) => compact.to_playback(),
+        };
+        playback.config.validate().map_err(|e| format!("{path}: {e}"))?;
+        Ok(playback)
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.playback_events.iter().filter(|e| matches!(e, PlaybackEvents::MoveSnake(_))).count()
+    }
+
+    /// The events up to and including the `moves`-th move, along with any apple placement or game
+    /// over that move caused, i.e. everything needed to replay the game to that point.
+    pub fn prefix_through_move(&self, moves: usize) -> &[PlaybackEvents] {
+        let mut moves_seen = 0;
+        for (i, event) in self.playback_events.iter().enumerate() {
+            if let PlaybackEvents::MoveSnake(_) = event {
+                if moves_seen == moves { return &self.playback_events[..i]; }
+                moves_seen += 1;
+            }
+        }
+        &self.playback_events
+    }
+}
+
+
+/// Where two playbacks' games part ways, e.g. a new champion's game and the previous champion's.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PlaybackDiff {
+    /// How many moves the games share before they differ (in a move, or in where the snake or
+    /// an apple was placed), or None if they're identical.
+    pub first_different_move: Option<usize>,
+    pub moves:  (usize, usize),
+    pub apples: (usize, usize),
+}
+
+impl Playback {
+    pub fn diff(&self, other: &Playback) -> PlaybackDiff {
+        let (a, b) = (&self.playback_events, &other.playback_events);
+        let shared_events = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+        let first_different_move = (a.len() != b.len() || shared_events != a.len())
+            .then(|| a[..shared_events].iter().filter(|e| matches!(e, PlaybackEvents::MoveSnake(_))).count());
+        PlaybackDiff {
+            first_different_move,
+            moves:  (self.move_count(), other.move_count()),
+            apples: (SnakeGame::from_playback_with_config(&self.config, a).apples_eaten, SnakeGame::from_playback_with_config(&other.config, b).apples_eaten),
+        }
+    }
+}
+
+/// A single game's `Playback` in far less space: where the snake started, where each apple went,
+/// and each move packed into 2 bits, as a turn relative to the previous move.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct CompactPlayback {
+    pub snake: (GridPoint, GridPoint),      // (head, tail) at the start
+    pub apples: Vec<(usize, GridPoint)>,    // (moves made before it was placed, location)
+    pub move_count: usize,
+    pub turns: Vec<u8>,                     // 4 per byte, low bits first: 0 straight, 1 right, 2 back, 3 left
+    pub is_game_over: bool,
+    #[serde(default)]
+    pub config: GameConfig,
+}
+
+impl Playback {
+    /// Packs a single game's events (a `NewGame` and the snake's location, then moves, apples, and
+    /// possibly a `GameOver`) into a `CompactPlayback`.
+    pub fn to_compact(&self) -> Result<CompactPlayback, String> {
+        let mut events = self.playback_events.iter();
+        let (Some(PlaybackEvents::NewGame), Some(&PlaybackEvents::NewSnakeLocation(head, tail))) = (events.next(), events.next()) else {
+            return Err("a playback must start with NewGame and NewSnakeLocation".to_string());
+        };
+        let mut compact = CompactPlayback { snake: (head, tail), apples: Vec::new(), move_count: 0, turns: Vec::new(), is_game_over: false, config: self.config.clone() };
+        let mut heading = Direction::between(tail, head).unwrap_or(Direction::North);
+        for event in events {
+            if compact.is_game_over { return Err("a playback can't go on after GameOver".to_string()); }
+            match *event {
+                PlaybackEvents::NewAppleLocation(pt) => compact.apples.push((compact.move_count, pt)),
+                PlaybackEvents::MoveSnake(direction) => {
+                    let turn = (direction.to_index() + 4 - heading.to_index()) % 4;
+                    if compact.move_count % 4 == 0 { compact.turns.push(0); }
+                    *compact.turns.last_mut().unwrap() |= (turn as u8) << (2 * (compact.move_count % 4));
+                    compact.move_count += 1;
+                    heading = direction;
+                }
+                PlaybackEvents::GameOver => compact.is_game_over = true,
+                PlaybackEvents::NewGame | PlaybackEvents::NewSnakeLocation(..) => return Err("a compact playback holds only one game".to_string()),
+            }
+        }
+        Ok(compact)
+    }
+}
+
+impl CompactPlayback {
+    /// Unpacks the events `Playback::to_compact()` packed.
+    pub fn to_playback(&self) -> Playback {
+        let (head, tail) = self.snake;
+        let mut events = vec![PlaybackEvents::NewGame, PlaybackEvents::NewSnakeLocation(head, tail)];
+        let mut apples = self.apples.iter().peekable();
+        let mut heading = Direction::between(tail, head).unwrap_or(Direction::North);
+        for moves in 0..=self.move_count {
+            if moves > 0 {
+                let i = moves - 1;
+                let turn = (self.turns.get(i / 4).copied().unwrap_or(0) >> (2 * (i % 4))) & 3;
+                heading = Direction::from_index((heading.to_index() + turn as usize) % 4);
+                events.push(PlaybackEvents::MoveSnake(heading));
+            }
+            while let Some(&(_, pt)) = apples.next_if(|(apple_moves, _)| *apple_moves == moves) {
+                events.push(PlaybackEvents::NewAppleLocation(pt));
+            }
+        }
+        if self.is_game_over { events.push(PlaybackEvents::GameOver); }
+        Playback { playback_events: events, config: self.config.clone() }
+    }
+}
+
+impl fmt::Display for PlaybackDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.first_different_move {
+            None    => writeln!(f, "The playbacks are identical")?,
+            Some(i) => writeln!(f, "The playbacks diverge after {i} shared moves")?,
+        }
+        writeln!(f, "Moves:  {} vs {}", self.moves.0, self.moves.1)?;
+        writeln!(f, "Apples: {} vs {}", self.apples.0, self.apples.1)
+    }
+}
+
+
+/// A hand-made starting position (e.g. the snake next to a wall), loaded by `SnakeGame::from_level_json()`.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct Level {
+    pub width:     i16,             // Must be Grid::WIDTH
+    pub height:    i16,             // Must be Grid::HEIGHT
+    pub obstacles: Vec<GridPoint>,  // Walls in addition to the perimeter
+    pub snake:     Vec<GridPoint>,  // Head first; at least two contiguous cells
+    pub apple:     GridPoint,
+}
+
+impl Level {
+    /// Checks that the level describes a playable game, i.e. everything is inside the perimeter,
+    /// the snake is contiguous and doesn't overlap itself, and the apple is on an empty cell.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width != Grid::WIDTH || self.height != Grid::HEIGHT {
+            return Err(format!("level is {}x{}, but the grid is {}x{}", self.width, self.height, Grid::WIDTH, Grid::HEIGHT));
+        }
+        let is_inside = |pt: GridPoint| pt.x > 0 && pt.y > 0 && pt.x < Grid::WIDTH - 1 && pt.y < Grid::HEIGHT - 1;
+        if let Some(pt) = self.obstacles.iter().find(|&&pt| !is_inside(pt)) { return Err(format!("obstacle {pt:?} is not inside the walls")); }
+        if self.snake.len() < 2 { return Err(format!("snake must have at least 2 cells, not {}", self.snake.len())); }
+        for (i, &pt) in self.snake.iter().enumerate() {
+            if !is_inside(pt) { return Err(format!("snake cell {pt:?} is not inside the walls")); }
+            if self.obstacles.contains(&pt) { return Err(format!("snake cell {pt:?} is on an obstacle")); }
+            if self.snake[..i].contains(&pt) { return Err(format!("snake cell {pt:?} appears twice")); }
+            if i > 0 {
+                let step = pt - self.snake[i - 1];
+                if step.x.abs() + step.y.abs() != 1 { return Err(format!("snake cells {:?} and {pt:?} aren't adjacent", self.snake[i - 1])); }
+            }
+        }
+        if !is_inside(self.apple) || self.obstacles.contains(&self.apple) || self.snake.contains(&self.apple) {
+            return Err(format!("apple {:?} is not on an empty cell", self.apple));
+        }
+        Ok(())
+    }
+}
+
+
+/// The settings a game is played under, recorded in its `Playback` so that it replays on the same
+/// board and by the same rules.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+    pub width:            i16,              // Must be Grid::WIDTH
+    pub height:           i16,              // Must be Grid::HEIGHT
+    pub grow_increment:   usize,
+    pub starvation_limit: Option<usize>,
+    pub max_moves:        Option<usize>,
+    pub reversal_rule:    ReversalRule,
+    pub initial_to_grow:  usize,
+    pub obstacles:        Vec<GridPoint>,
+}
+
+impl Default for GameConfig {
+    /// The settings of a `SnakeGame::new()`.
+    fn default() -> Self {
+        Self {
+            width: Grid::WIDTH,
+            height: Grid::HEIGHT,
+            grow_increment: SnakeGame::GROW_INCREMENT,
+            starvation_limit: None,
+            max_moves: None,
+            reversal_rule: ReversalRule::Crash,
+            initial_to_grow: 0,
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Checks that a game can be played under this config, i.e. on this build's grid.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width != Grid::WIDTH || self.height != Grid::HEIGHT {
+            return Err(format!("config is for a {}x{} grid, but the grid is {}x{}", self.width, self.height, Grid::WIDTH, Grid::HEIGHT));
+        }
+        let is_inside = |pt: GridPoint| pt.x > 0 && pt.y > 0 && pt.x < Grid::WIDTH - 1 && pt.y < Grid::HEIGHT - 1;
+        if let Some(pt) = self.obstacles.iter().find(|&&pt| !is_inside(pt)) { return Err(format!("obstacle {pt:?} is not inside the walls")); }
+        Ok(())
+    }
 }
 
 
@@ -255,12 +610,28 @@ pub struct SnakeGame {
     pub apple: Apple,
     pub apples_eaten: usize,
     pub state: GameState,
+    pub crash_reason: Option<CrashReason>,  // Set once state is GameOver
     pub playback: Playback,
     pub visited_vector: Vec<bool>,
     pub points_visited: usize,
+    pub covered_vector: Vec<bool>,     // Like visited_vector, but only cleared on restart()
+    pub cells_covered: usize,
+    free_cells: usize,                 // Non-wall cells (i.e. less any obstacles), counted on restart()
+    pub apple_policy: ApplePolicy,
+    apple_sequence_next: usize,        // Index into ApplePolicy::Sequence; reset on restart()
+    pub moves_since_last_apple: usize,
+    pub starvation_limit: Option<usize>,    // If set, the game ends after more moves than this without eating
+    pub max_moves: Option<usize>,           // If set, the game ends once moves_made reaches this
+    pub moves_made: usize,                  // Moves since the game (re)started
+    pub grow_increment: usize,              // Segments the snake grows per apple; GROW_INCREMENT by default
+    pub opponents: Vec<Opponent>,           // Other snakes on the board; none by default, and removed by restart()
+    pub reversal_rule: ReversalRule,
+    pub initial_to_grow: usize,             // Segments the snake grows over its first moves after restart(), e.g. to practice mid-game
+    pub obstacles: Vec<GridPoint>,          // Walls in addition to the perimeter, placed by every restart()
 }
 
 impl SnakeGame {
+    /// The default `grow_increment`.
     pub const GROW_INCREMENT: usize = 5;
 
     pub fn new(new_apple_location: Option<GridPoint>) -> Self {
@@ -280,19 +651,48 @@ impl SnakeGame {
             apple,
             apples_eaten: 0,
             state: GameState::Running,
-            playback: Playback { playback_events: Vec::with_capacity(256) },
+            crash_reason: None,
+            playback: Playback { playback_events: Vec::with_capacity(256), config: GameConfig::default() },
             visited_vector: vec![false; Grid::WIDTH as usize * Grid::HEIGHT as usize],
             points_visited: 0,
+            covered_vector: vec![false; Grid::WIDTH as usize * Grid::HEIGHT as usize],
+            cells_covered: 0,
+            free_cells: Grid::FREE_CELL_COUNT,
+            apple_policy: ApplePolicy::RandomEachEat,
+            apple_sequence_next: 0,
+            moves_since_last_apple: 0,
+            starvation_limit: None,
+            max_moves: None,
+            moves_made: 0,
+            grow_increment: Self::GROW_INCREMENT,
+            opponents: Vec::new(),
+            reversal_rule: ReversalRule::Crash,
+            initial_to_grow: 0,
+            obstacles: Vec::new(),
         };
         new_grid.playback.playback_events.clear();
         new_grid.playback.playback_events.push(PlaybackEvents::NewGame);
+        new_grid.playback.playback_events.push(PlaybackEvents::NewSnakeLocation(new_grid.snake.locations[0], new_grid.snake.locations[1]));
         new_grid.playback.playback_events.push(PlaybackEvents::NewAppleLocation(apple.location));
         new_grid
     }
 
     pub fn restart(&mut self, new_apple_location: Option<GridPoint>) {
+        self.restart_with(None, new_apple_location);
+    }
+
+    /// Like `restart()`, but optionally places the snake at (head, tail) instead of at random.
+    fn restart_with(&mut self, snake_location: Option<(GridPoint, GridPoint)>, new_apple_location: Option<GridPoint>) {
         self.grid.restart();
-        self.snake.restart(&mut self.grid);
+        for &pt in &self.obstacles {
+            self.grid.get_cell_mut(pt).kind = CellKind::Wall;
+        }
+        self.free_cells = self.grid.count_kind(CellKind::Empty);
+        match snake_location {
+            None => self.snake.restart(&mut self.grid),
+            Some((head, tail)) => self.snake.restart_at(&mut self.grid, head, tail),
+        }
+        self.snake.to_grow = self.initial_to_grow;
         self.apple.location = match new_apple_location {
             None => self.grid.new_viable_apple_location(),
             Some(pt) => pt,
@@ -301,17 +701,165 @@ impl SnakeGame {
         apple_cell.kind = CellKind::Apple;
         self.apples_eaten = 0;
         self.state = GameState::Running;
+        self.crash_reason = None;
         self.playback.playback_events.clear();
         self.playback.playback_events.push(PlaybackEvents::NewGame);
+        self.playback.playback_events.push(PlaybackEvents::NewSnakeLocation(self.snake.locations[0], self.snake.locations[1]));
         self.playback.playback_events.push(PlaybackEvents::NewAppleLocation(self.apple.location));
+        self.playback.config = self.config();
         self.clear_visited();
         self.points_visited = 0;
+        self.covered_vector.fill(false);
+        self.cells_covered = 0;
+        self.apple_sequence_next = 0;
+        self.moves_since_last_apple = 0;
+        self.moves_made = 0;
+        self.opponents.clear();
+    }
+
+    /// Where the next apple goes after one is eaten, according to the apple_policy.
+    fn next_apple_location(&mut self) -> GridPoint {
+        let ahead = self.snake.heading().map(|dir| self.snake.head_location + dir.to_point());
+        match &self.apple_policy {
+            ApplePolicy::RandomEachEat => self.grid.new_viable_apple_location(),
+            ApplePolicy::RandomNotAhead => self.grid.new_viable_apple_location_where(|pt| Some(pt) != ahead),
+            ApplePolicy::Ahead => match ahead {
+                Some(pt) if self.grid.get_cell(pt).kind == CellKind::Empty => pt,
+                _ => self.grid.new_viable_apple_location(),
+            },
+            &ApplePolicy::Fixed(pt) => self.grid.viable_apple_location_at_or_after(pt),
+            ApplePolicy::Sequence(points) => {
+                if points.is_empty() { return self.grid.new_viable_apple_location(); }
+                let pt = points[self.apple_sequence_next % points.len()];
+                self.apple_sequence_next += 1;
+                self.grid.viable_apple_location_at_or_after(pt)
+            }
+        }
+    }
+
+    /// The settings this game is played under (see `Playback::config`).
+    pub fn config(&self) -> GameConfig {
+        GameConfig {
+            width: self.grid.width,
+            height: self.grid.height,
+            grow_increment: self.grow_increment,
+            starvation_limit: self.starvation_limit,
+            max_moves: self.max_moves,
+            reversal_rule: self.reversal_rule,
+            initial_to_grow: self.initial_to_grow,
+            obstacles: self.obstacles.clone(),
+        }
+    }
+
+    /// Adopts `config`'s settings.  Its obstacles and initial_to_grow take effect at the next restart().
+    pub fn apply_config(&mut self, config: &GameConfig) {
+        self.grow_increment   = config.grow_increment;
+        self.starvation_limit = config.starvation_limit;
+        self.max_moves        = config.max_moves;
+        self.reversal_rule    = config.reversal_rule;
+        self.initial_to_grow  = config.initial_to_grow;
+        self.obstacles        = config.obstacles.clone();
+    }
+
+    /// Reconstructs a game, played under the default config, by replaying `events`.  See
+    /// `from_playback_with_config()`.
+    pub fn from_playback(events: &[PlaybackEvents]) -> SnakeGame {
+        Self::from_playback_with_config(&GameConfig::default(), events)
+    }
+
+    /// Reconstructs a game played under `config` (e.g. a `Playback::config`) by replaying `events`
+    /// (e.g. a whole `Playback`, or a `Playback::prefix_through_move()`).  The new game's own
+    /// playback will match `events`.
+    pub fn from_playback_with_config(config: &GameConfig, events: &[PlaybackEvents]) -> SnakeGame {
+        let mut game = SnakeGame::new(None);
+        game.apply_config(config);
+        let mut snake_location = None;
+        let mut is_restart_pending = false;
+        let mut i = 0;
+        while i < events.len() {
+            match events[i] {
+                PlaybackEvents::NewGame => is_restart_pending = true,
+                PlaybackEvents::NewSnakeLocation(head, tail) => snake_location = Some((head, tail)),
+                PlaybackEvents::NewAppleLocation(pt) => if is_restart_pending {
+                    game.restart_with(snake_location, Some(pt));
+                    is_restart_pending = false;
+                },
+                PlaybackEvents::MoveSnake(direction) => {
+                    // An apple placement right after a move is where that move's eaten apple went
+                    let new_apple_location = match events.get(i + 1) {
+                        Some(&PlaybackEvents::NewAppleLocation(pt)) => { i += 1; Some(pt) }
+                        _ => None,
+                    };
+                    game.move_snake(direction, new_apple_location);
+                }
+                // Already recorded by move_snake(), unless the snake starved (or hit a move limit)
+                // under a limit this game doesn't have.  The playback doesn't say which.
+                PlaybackEvents::GameOver => if game.state == GameState::Running { game.end_game(CrashReason::Starved); },
+            }
+            i += 1;
+        }
+        game
+    }
+
+    /// A new game whose snake and apple placements all come from `seed`, so different players
+    /// (e.g. Nets) can be compared on exactly the same boards.
+    pub fn from_seed(seed: u64) -> SnakeGame {
+        Self::from_seed_with_config(&GameConfig::default(), seed)
+    }
+
+    /// Like `from_seed()`, but played under `config`, which the playback records.  The snake and
+    /// first apple are placed clear of `config`'s obstacles.
+    pub fn from_seed_with_config(config: &GameConfig, seed: u64) -> SnakeGame {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut rand_point = |min_x| GridPoint::new(rng.gen_range(min_x..Grid::WIDTH - 1), rng.gen_range(1..Grid::HEIGHT - 1));
+        let is_open = |pt: GridPoint| !config.obstacles.contains(&pt);
+        let (head, tail) = loop {
+            let head = rand_point(2);
+            let tail = head - Direction::East.to_point();
+            if is_open(head) && is_open(tail) { break (head, tail); }
+        };
+        let apples = (0..256).map(|_| rand_point(1)).collect::<Vec<_>>();
+        let apple = apples.iter().copied().find(|&pt| pt != head && pt != tail && is_open(pt)).unwrap();
+        let mut game = Self::from_playback_with_config(config, &[
+            PlaybackEvents::NewGame,
+            PlaybackEvents::NewSnakeLocation(head, tail),
+            PlaybackEvents::NewAppleLocation(apple),
+        ]);
+        game.apple_policy = ApplePolicy::Sequence(apples);
+        game
+    }
+
+    /// Starts a game from a `Level` saved as JSON at `path`.  Note that the playback only
+    /// records the snake's first two cells, and `restart()` starts over from a random position
+    /// (among the same obstacles).
+    pub fn from_level_json(path: &str) -> Result<SnakeGame, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        let level: Level = serde_json::from_str(&s).map_err(|e| format!("parsing {path}: {e}"))?;
+        Self::from_level(&level).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn from_level(level: &Level) -> Result<SnakeGame, String> {
+        level.validate()?;
+        let mut game = SnakeGame::new(None);
+        game.obstacles = level.obstacles.clone();
+        game.restart_with(Some((level.snake[0], level.snake[1])), Some(level.apple));
+        for &pt in &level.snake[2..] {
+            game.grid.get_cell_mut(pt).kind = CellKind::Snake;
+            game.snake.locations.push_back(pt);
+        }
+        Ok(game)
     }
 
     pub fn clear_visited(&mut self) {
         self.visited_vector.fill(false);
     }
 
+    /// Fraction (0.0..=1.0) of the free (non-wall, non-obstacle) cells the snake's head has
+    /// entered this game, so that runs on different board sizes can be compared.
+    pub fn coverage(&self) -> f32 {
+        self.cells_covered as f32 / self.free_cells as f32
+    }
+
     /// Move the snake, typically from user input.  For playback, `new_apple_location` allows
     /// provision of where the next apple tile is place.  For live play, `new_apple_location`
     /// sould be `None`, in which case a random location is chosen.
@@ -331,51 +879,106 @@ impl SnakeGame {
     pub fn move_snake(&mut self, direction: Direction, new_apple_location: Option<GridPoint>) {
         //info!("move_snake({direction:#?}, {new_apple_location:#?}); snake.to_grow={}; GameState={:?}", self.snake.to_grow, self.state);
         if self.state != GameState::Running { return; }
-        self.playback.playback_events.push(PlaybackEvents::MoveSnake(direction));
-
-        // Move tail first, if needed
-        if self.snake.to_grow == 0 {
-            // Snake keeps same size, so we must pop off the tail to keep the same length, we will shortly push on a new head.
-            let old_tail_location = self.snake.locations.pop_back().unwrap();
-            let old_tail_cell = self.grid.get_cell_mut(old_tail_location);
-            old_tail_cell.kind = CellKind::Empty;
-        } else {
-            self.snake.to_grow -= 1;
+        let is_reversal = self.snake.heading() == Some(direction.opposite());
+        let direction = match self.reversal_rule {
+            ReversalRule::Forbidden if is_reversal => return,
+            ReversalRule::Ignore    if is_reversal => direction.opposite(),
+            _ => direction,
         };
+        self.playback.playback_events.push(PlaybackEvents::MoveSnake(direction));
 
-        let offset = direction.to_point();
-        let new_location = self.snake.head_location.add(offset);
-        let new_cell = self.grid.get_cell_mut(new_location);
-        let kind_hit = new_cell.kind;
-        new_cell.kind = CellKind::Snake;
+        let kind_hit = self.snake.step(&mut self.grid, direction);
+        let new_location = self.snake.head_location;
         match kind_hit {
             CellKind::Empty => {}
             CellKind::Apple => {
                 self.apples_eaten += 1;
-                self.apple.location = match new_apple_location {
-                    None => self.grid.new_viable_apple_location(),
-                    Some(pt) => pt,
-                };
-                let new_apple_cell = self.grid.get_cell_mut(self.apple.location);
-                new_apple_cell.kind = CellKind::Apple;
-                self.playback.playback_events.push(PlaybackEvents::NewAppleLocation(self.apple.location));
-                self.snake.to_grow += Self::GROW_INCREMENT;
+                self.place_next_apple(new_apple_location);
+                self.snake.to_grow += self.grow_increment;
             }
             _ => {
                 self.playback.playback_events.push(PlaybackEvents::GameOver);
                 self.state = GameState::GameOver;
-                new_cell.kind = CellKind::Crash;
+                self.crash_reason = Some(if kind_hit == CellKind::Wall { CrashReason::Wall } else { CrashReason::Snake });
+                self.grid.get_cell_mut(new_location).kind = CellKind::Crash;
             }
         };
-
-        // Push on new Head
-        self.snake.locations.push_front(new_location);
-        self.snake.head_location = new_location;
+        self.moves_since_last_apple = if kind_hit == CellKind::Apple { 0 } else { self.moves_since_last_apple + 1 };
+        if self.state == GameState::Running && self.starvation_limit.is_some_and(|limit| self.moves_since_last_apple > limit) {
+            self.end_game(CrashReason::Starved);
+        }
+        self.moves_made += 1;
+        if self.state == GameState::Running && self.max_moves.is_some_and(|max| self.moves_made >= max) {
+            self.end_game(CrashReason::MoveLimit);
+        }
 
         // Update visited info
         let i = new_location.y as usize * Grid::WIDTH as usize + new_location.x as usize;
         if !self.visited_vector[i] { self.points_visited += 1; }
         self.visited_vector[i] = true;
+        if !self.covered_vector[i] { self.cells_covered += 1; }
+        self.covered_vector[i] = true;
+    }
+
+
+    /// Puts the apple at `new_apple_location`, or where the apple_policy says if `None`.
+    fn place_next_apple(&mut self, new_apple_location: Option<GridPoint>) {
+        self.move_apple(new_apple_location);
+        self.playback.playback_events.push(PlaybackEvents::NewAppleLocation(self.apple.location));
+    }
+
+    /// Like `place_next_apple()`, but without recording it in the playback.
+    fn move_apple(&mut self, new_apple_location: Option<GridPoint>) {
+        self.apple.location = match new_apple_location {
+            None => self.next_apple_location(),
+            Some(pt) => pt,
+        };
+        self.grid.get_cell_mut(self.apple.location).kind = CellKind::Apple;
+    }
+
+    /// Adds an opponent snake at (head, tail), which must be adjacent empty cells, and returns its
+    /// index for `move_opponent()`.  Opponents' moves aren't recorded in the playback, and neither
+    /// is where an apple an opponent eats goes next, so a game with opponents can't be replayed
+    /// faithfully.
+    pub fn add_opponent(&mut self, head: GridPoint, tail: GridPoint) -> Result<usize, String> {
+        if !(head - tail).is_unit() {
+            return Err(format!("opponent head {head:?} and tail {tail:?} aren't adjacent"));
+        }
+        if [head, tail].iter().any(|&pt| !self.grid.is_in_bounds(pt) || self.grid.get_cell(pt).kind != CellKind::Empty) {
+            return Err(format!("no room for an opponent at {head:?}, {tail:?}"));
+        }
+        let mut snake = Snake { head_location: head, locations: VecDeque::new(), to_grow: 0 };
+        snake.restart_at(&mut self.grid, head, tail);
+        self.opponents.push(Opponent { snake, state: GameState::Running, apples_eaten: 0 });
+        Ok(self.opponents.len() - 1)
+    }
+
+    /// Moves opponent `index`, like `move_snake()` does the player's snake.  Crashing (into a wall
+    /// or any snake) ends only that opponent's game; its body stays on the board.
+    pub fn move_opponent(&mut self, index: usize, direction: Direction) {
+        let opponent = &mut self.opponents[index];
+        if opponent.state != GameState::Running || self.state != GameState::Running { return; }
+        let kind_hit = opponent.snake.step(&mut self.grid, direction);
+        match kind_hit {
+            CellKind::Empty => {}
+            CellKind::Apple => {
+                opponent.apples_eaten += 1;
+                opponent.snake.to_grow += self.grow_increment;
+                // Not recorded, as the player's playback has no opponent move to attribute it to
+                self.move_apple(None);
+            }
+            _ => {
+                opponent.state = GameState::GameOver;
+                self.grid.get_cell_mut(opponent.snake.head_location).kind = CellKind::Crash;
+            }
+        }
+    }
+
+    /// Ends a game that's still running, other than by crashing.
+    fn end_game(&mut self, reason: CrashReason) {
+        self.playback.playback_events.push(PlaybackEvents::GameOver);
+        self.state = GameState::GameOver;
+        self.crash_reason = Some(reason);
     }
 
 
@@ -391,6 +994,45 @@ impl SnakeGame {
         }
         (dist_walls, dist_snake)
     }
+    /// How many empty (or apple) cells the head can reach, by flood fill, if the body stays put.
+    /// A small number means the snake has boxed itself in.
+    pub fn reachable_free_space(&self) -> usize {
+        let mut seen = vec![false; self.grid.cells.len()];
+        let mut to_visit = vec![self.snake.head_location];
+        let mut count = 0;
+        while let Some(pt) = to_visit.pop() {
+            for dir in [Direction::North, Direction::East, Direction::South, Direction::West] {
+                let next = pt + dir.to_point();
+                if !matches!(self.grid.get_cell(next).kind, CellKind::Empty | CellKind::Apple) { continue; }
+                let i = next.y as usize * Grid::WIDTH as usize + next.x as usize;
+                if seen[i] { continue; }
+                seen[i] = true;
+                count += 1;
+                to_visit.push(next);
+            }
+        }
+        count
+    }
+
+    /// The directions the snake can move in without crashing right away, never including straight
+    /// back.  Moving onto the tail is fine unless the snake is growing, since it moves out of the way.
+    pub fn legal_moves(&self) -> Vec<Direction> {
+        let head = self.snake.head_location;
+        let neck = self.snake.locations.get(1).copied();
+        let tail = self.snake.locations.back().copied();
+        [Direction::North, Direction::East, Direction::South, Direction::West].into_iter()
+            .filter(|dir| {
+                let pt = head + dir.to_point();
+                if Some(pt) == neck { return false; }
+                match self.grid.get_cell(pt).kind {
+                    CellKind::Empty | CellKind::Apple => true,
+                    CellKind::Snake => Some(pt) == tail && self.snake.to_grow == 0,
+                    CellKind::Wall | CellKind::Crash => false,
+                }
+            })
+            .collect()
+    }
+
     fn distance_to(&self, pt_start: GridPoint, direction: Direction, target: CellKind) -> i16 {
         let offset = direction.to_point();
         let mut distance = 0;
@@ -401,4 +1043,485 @@ impl SnakeGame {
         }
         distance
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a game whose snake occupies exactly `locations` (head first) and whose apple is at
+    /// `apple`, so tests don't depend on the random starting position.
+    fn game_with_snake(locations: &[GridPoint], apple: GridPoint) -> SnakeGame {
+        let mut game = SnakeGame::new(Some(apple));
+        game.grid.restart();
+        game.snake.locations.clear();
+        for &pt in locations {
+            game.grid.get_cell_mut(pt).kind = CellKind::Snake;
+            game.snake.locations.push_back(pt);
+        }
+        game.snake.head_location = locations[0];
+        game.snake.to_grow = 0;
+        game.apple.location = apple;
+        game.grid.get_cell_mut(apple).kind = CellKind::Apple;
+        game
+    }
+
+    /// Moves back and forth along rows 1..=rows (a boustrophedon), never revisiting a cell.
+    fn serpentine(game: &mut SnakeGame, rows: i16, new_apple_location: GridPoint) {
+        for row in 1..=rows {
+            let dir = if row % 2 == 1 { Direction::East } else { Direction::West };
+            for _ in 0..(Grid::WIDTH - 3) { game.move_snake(dir, Some(new_apple_location)); }
+            if row < rows { game.move_snake(Direction::North, Some(new_apple_location)); }
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_when_boxed_in() {
+        let far_away = GridPoint::new(30, 25);
+        // Wall to the west, neck to the east, and body (ending in the tail, when `tail_len` = 0) to the north
+        let game_of = |tail_len: i16| {
+            let mut locations = vec![GridPoint::new(1, 10), GridPoint::new(2, 10), GridPoint::new(2, 11), GridPoint::new(1, 11)];
+            locations.extend((0..tail_len).map(|i| GridPoint::new(1, 12 + i)));
+            game_with_snake(&locations, far_away)
+        };
+        assert_eq!(vec![Direction::South], game_of(2).legal_moves());
+        let mut game = game_of(0);
+        assert_eq!(vec![Direction::North, Direction::South], game.legal_moves());
+        game.snake.to_grow = 1;
+        assert_eq!(vec![Direction::South], game.legal_moves());
+    }
+
+    #[test]
+    fn test_initial_to_grow() {
+        let far_away = GridPoint::new(30, 25);
+        let mut game = SnakeGame::new(None);
+        game.initial_to_grow = 3;
+        game.restart_with(Some((GridPoint::new(5, 10), GridPoint::new(4, 10))), Some(far_away));
+        let mut lengths = Vec::new();
+        for _ in 0..5 {
+            game.move_snake(Direction::East, None);
+            lengths.push(game.snake.length());
+        }
+        assert_eq!(GameState::Running, game.state);
+        assert_eq!(vec![3, 4, 5, 5, 5], lengths);
+        assert_eq!(0, game.apples_eaten);
+    }
+
+    #[test]
+    fn test_reachable_free_space() {
+        let far_away = GridPoint::new(30, 25);
+        let game = game_with_snake(&[GridPoint::new(1, 2), GridPoint::new(2, 2)], far_away);
+        assert_eq!(Grid::FREE_CELL_COUNT - 2, game.reachable_free_space());
+        // Walled into the 2x2 corner, the snake leaves only the other two cells free
+        let mut game = game_with_snake(&[GridPoint::new(2, 2), GridPoint::new(2, 1)], far_away);
+        for pt in [GridPoint::new(3, 1), GridPoint::new(3, 2), GridPoint::new(1, 3), GridPoint::new(2, 3)] {
+            game.grid.get_cell_mut(pt).kind = CellKind::Wall;
+        }
+        assert_eq!(2, game.reachable_free_space());
+    }
+
+    #[test]
+    fn test_playback_replays_under_its_config() {
+        let mut game = SnakeGame::new(None);
+        game.grow_increment = 2;
+        game.obstacles = vec![GridPoint::new(9, 5)];
+        game.restart_with(Some((GridPoint::new(5, 5), GridPoint::new(4, 5))), Some(GridPoint::new(6, 5)));
+        for _ in 0..4 { game.move_snake(Direction::East, Some(GridPoint::new(30, 25))); }
+        assert_eq!((GameState::GameOver, Some(CrashReason::Wall), 4), (game.state, game.crash_reason, game.snake.length()));
+
+        let json = serde_json::to_string(&game.playback).unwrap();
+        let playback: Playback = serde_json::from_str(&json).unwrap();
+        assert_eq!(game.config(), playback.config);
+        let replayed = SnakeGame::from_playback_with_config(&playback.config, &playback.playback_events);
+        assert_eq!((game.state, game.crash_reason, game.snake.length()), (replayed.state, replayed.crash_reason, replayed.snake.length()));
+        // Under the defaults, there's no obstacle to hit, so the snake grows longer, and the
+        // recorded end can only be taken for starvation
+        let replayed = SnakeGame::from_playback(&playback.playback_events);
+        assert_eq!((GameState::GameOver, Some(CrashReason::Starved), 5), (replayed.state, replayed.crash_reason, replayed.snake.length()));
+
+        // Files from before configs were recorded replay under the defaults
+        let old: Playback = serde_json::from_str(r#"{"playback_events":["NewGame"]}"#).unwrap();
+        assert_eq!(GameConfig::default(), old.config);
+    }
+
+    #[test]
+    fn test_seeded_board_avoids_obstacles() {
+        let unobstructed = SnakeGame::from_seed(7);
+        let (head, tail) = (unobstructed.snake.head_location, unobstructed.snake.locations[1]);
+        let config = GameConfig { obstacles: vec![head, tail, unobstructed.apple.location], ..Default::default() };
+        let game = SnakeGame::from_seed_with_config(&config, 7);
+        for &pt in &config.obstacles {
+            assert_eq!(CellKind::Wall, game.grid.get_cell(pt).kind);
+        }
+        assert!(!config.obstacles.contains(&game.snake.head_location) && !config.obstacles.contains(&game.snake.locations[1]));
+        assert!(!config.obstacles.contains(&game.apple.location));
+        assert_eq!(config, game.playback.config);
+        assert_eq!(game.playback.playback_events, SnakeGame::from_seed_with_config(&config, 7).playback.playback_events);
+    }
+
+    #[test]
+    fn test_coverage_excludes_obstacles() {
+        let mut game = SnakeGame::new(None);
+        game.obstacles = (1..11).map(|x| GridPoint::new(x, 10)).collect();
+        game.restart(None);
+        game.cells_covered = Grid::FREE_CELL_COUNT - 10;
+        assert_eq!(1.0, game.coverage());
+    }
+
+    #[test]
+    fn test_free_cell_count() {
+        let grid = Grid::new();
+        let free = grid.cells.iter().filter(|c| c.kind != CellKind::Wall).count();
+        assert_eq!(free, Grid::FREE_CELL_COUNT);
+    }
+
+    #[test]
+    fn test_count_kind_on_fresh_grid() {
+        let grid = Grid::new();
+        let perimeter = 2 * (Grid::WIDTH as usize + Grid::HEIGHT as usize) - 4;
+        assert_eq!(perimeter, grid.count_kind(CellKind::Wall));
+        assert_eq!(Grid::FREE_CELL_COUNT, grid.count_kind(CellKind::Empty));
+        assert_eq!(0, grid.count_kind(CellKind::Snake) + grid.count_kind(CellKind::Apple) + grid.count_kind(CellKind::Crash));
+    }
+
+    #[test]
+    fn test_coverage_half() {
+        let far_away = GridPoint::new(30, 25);
+        let mut game = game_with_snake(&[GridPoint::new(1, 1), GridPoint::new(1, 2)], far_away);
+        assert_eq!(0.0, game.coverage());
+        // Half of the 28 interior rows; the starting head cell is never "entered", hence the -1.
+        serpentine(&mut game, (Grid::HEIGHT - 2) / 2, far_away);
+        assert_eq!(GameState::Running, game.state);
+        assert_eq!(Grid::FREE_CELL_COUNT / 2 - 1, game.cells_covered);
+        assert!((0.5 - game.coverage()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_coverage_ignores_clear_visited() {
+        let far_away = GridPoint::new(30, 25);
+        let mut game = game_with_snake(&[GridPoint::new(1, 1), GridPoint::new(1, 2)], GridPoint::new(5, 1));
+        // Loop around rows 1 & 2 twice, eating the apple (and clearing visited, as run_one_game
+        // does) on the first lap, so the second lap re-counts cells in points_visited.
+        for _ in 0..2 {
+            for _ in 0..9 {
+                let apples_before = game.apples_eaten;
+                game.move_snake(Direction::East, Some(far_away));
+                if apples_before != game.apples_eaten { game.clear_visited(); }
+            }
+            game.move_snake(Direction::North, Some(far_away));
+            for _ in 0..9 { game.move_snake(Direction::West, Some(far_away)); }
+            game.move_snake(Direction::South, Some(far_away));
+        }
+        assert_eq!(GameState::Running, game.state);
+        assert_eq!(1, game.apples_eaten);
+        assert!(game.points_visited > game.cells_covered);
+        assert_eq!(20, game.cells_covered);
+        assert!(game.coverage() <= 1.0);
+    }
+
+    #[test]
+    fn test_self_collision_is_game_over() {
+        let far_away = GridPoint::new(30, 25);
+        // Head at (2,2), with (3,2)--a body segment that is not the tail--directly East.
+        let locations = [GridPoint::new(2, 2), GridPoint::new(2, 1), GridPoint::new(3, 1), GridPoint::new(3, 2), GridPoint::new(3, 3)];
+        let mut game = game_with_snake(&locations, far_away);
+        game.move_snake(Direction::East, None);
+        assert_eq!(GameState::GameOver, game.state);
+        assert_eq!(CellKind::Crash, game.grid.get_cell(GridPoint::new(3, 2)).kind);
+        assert_eq!(Some(&PlaybackEvents::GameOver), game.playback.playback_events.last());
+    }
+
+    #[test]
+    fn test_wall_collision_is_game_over() {
+        let mut game = game_with_snake(&[GridPoint::new(1, 1), GridPoint::new(2, 1)], GridPoint::new(30, 25));
+        game.move_snake(Direction::West, None);
+        assert_eq!(GameState::GameOver, game.state);
+        assert_eq!(CellKind::Crash, game.grid.get_cell(GridPoint::new(0, 1)).kind);
+        assert_eq!(Some(CrashReason::Wall), game.crash_reason);
+    }
+
+    #[test]
+    fn test_following_tail_is_legal() {
+        // A 2x2 square: the head at (1,1) has the tail at (2,1) directly East.
+        let locations = [GridPoint::new(1, 1), GridPoint::new(1, 2), GridPoint::new(2, 2), GridPoint::new(2, 1)];
+        let mut game = game_with_snake(&locations, GridPoint::new(30, 25));
+        for dir in [Direction::East, Direction::North, Direction::West, Direction::South] {
+            game.move_snake(dir, None);
+            assert_eq!(GameState::Running, game.state);
+        }
+        assert_eq!(4, game.snake.length());
+        assert!(locations.iter().all(|&pt| game.grid.get_cell(pt).kind == CellKind::Snake));
+    }
+
+    #[test]
+    fn test_hitting_stationary_tail_while_growing_is_crash() {
+        let locations = [GridPoint::new(1, 1), GridPoint::new(1, 2), GridPoint::new(2, 2), GridPoint::new(2, 1)];
+        let mut game = game_with_snake(&locations, GridPoint::new(30, 25));
+        game.snake.to_grow = 1;
+        game.move_snake(Direction::East, None);
+        assert_eq!(GameState::GameOver, game.state);
+        assert_eq!(CellKind::Crash, game.grid.get_cell(GridPoint::new(2, 1)).kind);
+        assert_eq!(5, game.snake.length());     // The tail did not move
+    }
+
+    #[test]
+    fn test_from_playback_round_trip() {
+        let mut game = SnakeGame::new(None);
+        while game.state == GameState::Running && game.playback.move_count() < 500 {
+            game.move_snake(Direction::from_index(rand::thread_rng().gen_range(0..4)), None);
+        }
+        let replayed = SnakeGame::from_playback(&game.playback.playback_events);
+        assert_eq!(game.playback.playback_events, replayed.playback.playback_events);
+        assert_eq!(game.grid.cells, replayed.grid.cells);
+        assert_eq!(game.snake.locations, replayed.snake.locations);
+        assert_eq!(game.apples_eaten, replayed.apples_eaten);
+        assert_eq!(game.state, replayed.state);
+    }
+
+    #[test]
+    fn test_prefix_through_move() {
+        let mut game = game_with_snake(&[GridPoint::new(1, 1), GridPoint::new(1, 2)], GridPoint::new(3, 1));
+        game.playback.playback_events = vec![PlaybackEvents::NewGame, PlaybackEvents::NewSnakeLocation(GridPoint::new(1, 1), GridPoint::new(1, 2)), PlaybackEvents::NewAppleLocation(GridPoint::new(3, 1))];
+        for _ in 0..3 { game.move_snake(Direction::East, Some(GridPoint::new(30, 25))); }
+        assert_eq!(3, game.playback.move_count());
+        assert_eq!(3, game.playback.prefix_through_move(0).len());
+        // The second move ate the apple, so its NewAppleLocation belongs with it.
+        assert_eq!(Some(&PlaybackEvents::NewAppleLocation(GridPoint::new(30, 25))), game.playback.prefix_through_move(2).last());
+        assert_eq!(1, SnakeGame::from_playback(game.playback.prefix_through_move(2)).apples_eaten);
+        assert_eq!(game.playback.playback_events.len(), game.playback.prefix_through_move(99).len());
+    }
+
+    #[test]
+    fn test_apple_sequence_policy() {
+        let mut game = game_with_snake(&[GridPoint::new(2, 1), GridPoint::new(1, 1)], GridPoint::new(3, 1));
+        game.apple_policy = ApplePolicy::Sequence(vec![GridPoint::new(10, 10), GridPoint::new(4, 1), GridPoint::new(12, 12)]);
+        game.move_snake(Direction::East, None);
+        assert_eq!(GridPoint::new(10, 10), game.apple.location);
+        assert_eq!(GridPoint::new(4, 1), game.next_apple_location());
+        assert_eq!(GridPoint::new(12, 12), game.next_apple_location());
+        // Wraps around when exhausted, and since the apple is still at (10, 10), gives way to the next empty cell
+        assert_eq!(GridPoint::new(11, 10), game.next_apple_location());
+        game.restart(None);
+        assert_eq!(0, game.apple_sequence_next);
+    }
+
+    #[test]
+    fn test_starvation_ends_game() {
+        // Circling a 2x2 square never eats the far away apple
+        let locations = [GridPoint::new(1, 1), GridPoint::new(1, 2), GridPoint::new(2, 2), GridPoint::new(2, 1)];
+        let mut game = game_with_snake(&locations, GridPoint::new(30, 25));
+        game.starvation_limit = Some(6);
+        for dir in [Direction::East, Direction::North, Direction::West, Direction::South, Direction::East, Direction::North] {
+            game.move_snake(dir, None);
+        }
+        assert_eq!((GameState::Running, 6), (game.state, game.moves_since_last_apple));
+        game.move_snake(Direction::West, None);
+        assert_eq!(GameState::GameOver, game.state);
+        assert_eq!(Some(CrashReason::Starved), game.crash_reason);
+        assert_eq!(Some(&PlaybackEvents::GameOver), game.playback.playback_events.last());
+
+        // Eating resets the count
+        let mut game = game_with_snake(&[GridPoint::new(2, 1), GridPoint::new(1, 1)], GridPoint::new(4, 1));
+        game.starvation_limit = Some(1);
+        game.move_snake(Direction::East, Some(GridPoint::new(30, 25)));
+        game.move_snake(Direction::East, Some(GridPoint::new(30, 25)));
+        assert_eq!((GameState::Running, 0), (game.state, game.moves_since_last_apple));
+
+        // A replay, without the limit, still ends starved
+        let replayed = SnakeGame::from_playback(&[
+            PlaybackEvents::NewGame,
+            PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+            PlaybackEvents::NewAppleLocation(GridPoint::new(20, 20)),
+            PlaybackEvents::MoveSnake(Direction::East),
+            PlaybackEvents::GameOver,
+        ]);
+        assert_eq!((GameState::GameOver, Some(CrashReason::Starved)), (replayed.state, replayed.crash_reason));
+    }
+
+    #[test]
+    fn test_snakes_crash_into_each_other() {
+        let mut game = game_with_snake(&[GridPoint::new(6, 5), GridPoint::new(5, 5), GridPoint::new(4, 5)], GridPoint::new(30, 25));
+        let opponent = game.add_opponent(GridPoint::new(5, 7), GridPoint::new(5, 8)).unwrap();
+        assert!(game.add_opponent(GridPoint::new(5, 6), GridPoint::new(5, 5)).is_err());
+
+        // The opponent runs into the player's body, which ends only the opponent's game
+        game.move_opponent(opponent, Direction::South);
+        game.move_opponent(opponent, Direction::South);
+        assert_eq!(GameState::GameOver, game.opponents[opponent].state);
+        assert_eq!(GameState::Running, game.state);
+
+        // Then the player runs into the opponent's body
+        game.move_snake(Direction::North, None);
+        assert_eq!(GameState::Running, game.state);
+        // ...which it senses like its own body
+        assert_eq!(0, game.wall_and_body_distances().1[Direction::West.to_index()]);
+        game.move_snake(Direction::West, None);
+        assert_eq!((GameState::GameOver, Some(CrashReason::Snake)), (game.state, game.crash_reason));
+
+        game.restart(None);
+        assert!(game.opponents.is_empty());
+    }
+
+    #[test]
+    fn test_opponent_eating_isnt_recorded() {
+        let mut game = game_with_snake(&[GridPoint::new(6, 5), GridPoint::new(5, 5)], GridPoint::new(10, 11));
+        let opponent = game.add_opponent(GridPoint::new(10, 10), GridPoint::new(10, 9)).unwrap();
+        let events_before = game.playback.playback_events.len();
+        game.move_opponent(opponent, Direction::North);
+        assert_eq!(1, game.opponents[opponent].apples_eaten);
+        assert_ne!(GridPoint::new(10, 11), game.apple.location);
+        assert_eq!(CellKind::Apple, game.grid.get_cell(game.apple.location).kind);
+        assert_eq!(events_before, game.playback.playback_events.len());
+    }
+
+    #[test]
+    fn test_apple_sampler_skips_excluded_cells() {
+        let mut grid = Grid::new();
+        let (excluded, allowed) = (GridPoint::new(5, 5), GridPoint::new(20, 20));
+        for y in 1..Grid::HEIGHT - 1 {
+            for x in 1..Grid::WIDTH - 1 {
+                if ![excluded, allowed].contains(&GridPoint::new(x, y)) { grid.get_cell_mut(GridPoint::new(x, y)).kind = CellKind::Wall; }
+            }
+        }
+        for _ in 0..20 {
+            assert_eq!(allowed, grid.new_viable_apple_location_where(|pt| pt != excluded));
+        }
+        // With nowhere allowed, it settles for anywhere empty
+        assert!([excluded, allowed].contains(&grid.new_viable_apple_location_where(|_| false)));
+
+        // ApplePolicy::Ahead puts the next apple right in front of the snake
+        let mut game = game_with_snake(&[GridPoint::new(5, 5), GridPoint::new(4, 5)], GridPoint::new(6, 5));
+        game.apple_policy = ApplePolicy::Ahead;
+        game.move_snake(Direction::East, None);
+        assert_eq!(GridPoint::new(7, 5), game.apple.location);
+    }
+
+    #[test]
+    fn test_compact_playback_round_trips() {
+        let mut game = SnakeGame::new(None);
+        while game.state == GameState::Running && game.moves_made < 500 {
+            game.move_snake(Direction::from_index(rand::thread_rng().gen_range(0..4)), None);
+        }
+        let compact = game.playback.to_compact().unwrap();
+        assert_eq!(game.playback.move_count(), compact.move_count);
+        assert_eq!(game.playback.playback_events, compact.to_playback().playback_events);
+        assert!(serde_json::to_string(&compact).unwrap().len() < serde_json::to_string(&game.playback).unwrap().len());
+
+        let two_games = Playback { playback_events: [game.playback.playback_events.clone(), game.playback.playback_events.clone()].concat(), config: GameConfig::default() };
+        assert!(two_games.to_compact().is_err());
+    }
+
+    #[test]
+    fn test_reversal_rules() {
+        let locations = [GridPoint::new(5, 5), GridPoint::new(4, 5), GridPoint::new(3, 5)];
+        let play_reversal = |rule| {
+            let mut game = game_with_snake(&locations, GridPoint::new(30, 25));
+            game.reversal_rule = rule;
+            assert_eq!(Some(Direction::East), game.snake.heading());
+            let events_before = game.playback.playback_events.len();
+            game.move_snake(Direction::West, None);
+            (game.state, game.snake.head_location, game.playback.playback_events.len() - events_before)
+        };
+        assert_eq!((GameState::GameOver, GridPoint::new(4, 5), 2), play_reversal(ReversalRule::Crash));
+        assert_eq!((GameState::Running,  GridPoint::new(6, 5), 1), play_reversal(ReversalRule::Ignore));
+        assert_eq!((GameState::Running,  GridPoint::new(5, 5), 0), play_reversal(ReversalRule::Forbidden));
+    }
+
+    #[test]
+    fn test_move_limit_ends_game() {
+        let mut game = game_with_snake(&[GridPoint::new(2, 5), GridPoint::new(1, 5)], GridPoint::new(30, 25));
+        game.max_moves = Some(10);
+        let mut moves = 0;
+        while game.state == GameState::Running {
+            game.move_snake(Direction::East, None);
+            moves += 1;
+        }
+        assert_eq!((10, 10), (moves, game.moves_made));
+        assert_eq!(Some(CrashReason::MoveLimit), game.crash_reason);
+        assert_eq!(Some(&PlaybackEvents::GameOver), game.playback.playback_events.last());
+        game.restart(None);
+        assert_eq!(0, game.moves_made);
+    }
+
+    #[test]
+    fn test_playback_diff() {
+        let play = |dirs: &[Direction]| {
+            let mut game = SnakeGame::from_playback(&[
+                PlaybackEvents::NewGame,
+                PlaybackEvents::NewSnakeLocation(GridPoint::new(5, 5), GridPoint::new(4, 5)),
+                PlaybackEvents::NewAppleLocation(GridPoint::new(8, 5)),
+            ]);
+            for &dir in dirs { game.move_snake(dir, Some(GridPoint::new(20, 20))); }
+            game.playback
+        };
+        use Direction::*;
+        let a = play(&[East, East, East, North, North]);
+        let b = play(&[East, East, East, South, South]);
+        let c = play(&[East, North]);
+        assert_eq!(PlaybackDiff { first_different_move: Some(3), moves: (5, 5), apples: (1, 1) }, a.diff(&b));
+        assert_eq!(PlaybackDiff { first_different_move: Some(1), moves: (5, 2), apples: (1, 0) }, a.diff(&c));
+        assert_eq!(None, a.diff(&a).first_different_move);
+        assert_eq!(Some(5), a.diff(&play(&[East, East, East, North, North, West])).first_different_move);
+    }
+
+    #[test]
+    fn test_level_json_round_trip() {
+        let json = r#"{
+            "width": 40, "height": 30,
+            "obstacles": [{"x": 5, "y": 5}, {"x": 5, "y": 6}],
+            "snake": [{"x": 2, "y": 1}, {"x": 1, "y": 1}, {"x": 1, "y": 2}],
+            "apple": {"x": 38, "y": 28}
+        }"#;
+        let path = std::env::temp_dir().join(format!("snake-bevy-test-{}-Level.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(path, json).unwrap();
+        let game = SnakeGame::from_level_json(path);
+        std::fs::remove_file(path).unwrap();
+        let game = game.unwrap();
+
+        let level: Level = serde_json::from_str(json).unwrap();
+        assert_eq!(level.snake, game.snake.locations.iter().copied().collect::<Vec<_>>());
+        assert_eq!(level.apple, game.apple.location);
+        let empty_grid = Grid::new();
+        for x in 0..Grid::WIDTH {
+            for y in 0..Grid::HEIGHT {
+                let pt = GridPoint::new(x, y);
+                let expected = if level.obstacles.contains(&pt) || empty_grid.get_cell(pt).kind == CellKind::Wall {
+                    CellKind::Wall
+                } else if level.snake.contains(&pt) {
+                    CellKind::Snake
+                } else if pt == level.apple {
+                    CellKind::Apple
+                } else {
+                    CellKind::Empty
+                };
+                assert_eq!(expected, game.grid.get_cell(pt).kind, "{pt:?}");
+            }
+        }
+
+        let broken = Level { snake: vec![GridPoint::new(2, 1), GridPoint::new(1, 2)], ..level.clone() };
+        assert!(SnakeGame::from_level(&broken).unwrap_err().contains("adjacent"));
+        let broken = Level { apple: GridPoint::new(5, 6), ..level };
+        assert!(SnakeGame::from_level(&broken).is_err());
+    }
+
+    #[test]
+    fn test_snake_grows_by_grow_increment_then_holds() {
+        for grow_increment in [SnakeGame::GROW_INCREMENT, 0, 3] {
+            let mut game = game_with_snake(&[GridPoint::new(2, 5), GridPoint::new(1, 5)], GridPoint::new(3, 5));
+            game.grow_increment = grow_increment;
+            game.move_snake(Direction::East, Some(GridPoint::new(30, 25)));
+            assert_eq!(1, game.apples_eaten);
+            // The move that ate the apple grows nothing itself; each following move adds one segment
+            let mut lengths = vec![game.snake.length()];
+            for _ in 0..grow_increment + 3 {
+                game.move_snake(Direction::East, None);
+                lengths.push(game.snake.length());
+            }
+            assert_eq!(GameState::Running, game.state);
+            let expected = (0..=grow_increment + 3).map(|i| 2 + i.min(grow_increment)).collect::<Vec<_>>();
+            assert_eq!(expected, lengths, "grow_increment={grow_increment}");
+            assert_eq!(0, game.snake.to_grow);
+        }
+    }
+}